@@ -0,0 +1,27 @@
+use std::error::Error;
+use std::fs::remove_file;
+use git2::Repository;
+use structopt::StructOpt;
+use crate::model::*;
+
+#[derive(Debug, StructOpt)]
+pub struct Convert {
+    /// The format to convert the project file to: toml, json, or yaml.
+    #[structopt(long)]
+    to: Format,
+}
+
+pub fn convert(args: Convert) -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let mut git_project = GitProject::open()?;
+    if git_project.format() == args.to { return Ok(()) }
+
+    let original = git_project.path()?;
+    git_project.set_format(args.to);
+    git_project.save()?;
+    remove_file(&original)?;
+    args.to.update_config(&repository)?;
+
+    println!("Converted {} to {}", original.display(), git_project.path()?.display());
+    Ok(())
+}
@@ -1,14 +1,15 @@
-use std::env::current_dir;
 use std::error::Error;
 use git2::Repository;
 use crate::model::*;
+use crate::commands::TaskCommits;
 
 pub fn current() -> Result<(), Box<dyn Error>> {
-    let repository = Repository::discover(current_dir()?)?;
+    let repository = Repository::open_from_env()?;
     let config = repository.config()?.snapshot()?;
     let current_user = config.get_str("user.email")?;
 
     let git_project = GitProject::open()?;
+    let task_commits = TaskCommits::load(&repository)?;
     for project in git_project.projects() {
         let my_tasks: Vec<&Task> = project
             .tasks()
@@ -30,6 +31,14 @@ pub fn current() -> Result<(), Box<dyn Error>> {
             for line in task.description().lines() {
                 println!("\t{}", line);
             }
+            let commits = task_commits.of(project.id(), task.id());
+            if !commits.is_empty() {
+                println!();
+                println!("\tCommits:");
+                for (id, summary) in commits {
+                    println!("\t\x1b[33m{:.7}\x1b[0m {}", id, summary);
+                }
+            }
         }
     }
 
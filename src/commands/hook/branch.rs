@@ -0,0 +1,51 @@
+use std::error::Error;
+use git2::Repository;
+use regex::Regex;
+use crate::model::*;
+
+const BRANCH_PATTERN_CONFIG_KEY: &str = "project.branchPattern";
+/// Matches the last segment of the branch name, e.g. `login-form` in `feature/login-form`
+const DEFAULT_BRANCH_PATTERN: &str = r"^(?:[^/]+/)*([^\[\]\s@/]+)$";
+
+/// The name of the currently checked out branch, if any.
+pub fn current_branch(repository: &Repository) -> Result<Option<String>, Box<dyn Error>> {
+    let head = repository.find_reference("HEAD")?;
+    Ok(head.symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(str::to_string))
+}
+
+/// Finds the task that the current branch is named after, using the patterns configured in
+/// `project.branchPattern`. The task ID is taken from the capture group named `task` if there is
+/// one, or the first capture group otherwise. Only IDs of tasks that actually exist are returned.
+pub fn branch_task(repository: &Repository, git_project: &GitProject) -> Result<Option<Id>, Box<dyn Error>> {
+    let branch = match current_branch(repository)? {
+        Some(branch) => branch,
+        None => return Ok(None),
+    };
+
+    let config = repository.config()?.snapshot()?;
+    let mut patterns = vec![];
+    config.entries(Some(BRANCH_PATTERN_CONFIG_KEY))?.for_each(|entry| {
+        if let Some(pattern) = entry.value() { patterns.push(pattern.to_string()); }
+    })?;
+    if patterns.is_empty() {
+        patterns.push(DEFAULT_BRANCH_PATTERN.to_string());
+    }
+
+    for pattern in patterns {
+        let pattern = Regex::new(&pattern)?;
+        let captures = match pattern.captures(&branch) {
+            Some(captures) => captures,
+            None => continue,
+        };
+        let task_id: Id = match captures.name("task").or_else(|| captures.get(1)) {
+            Some(task_id) => task_id.as_str().into(),
+            None => continue,
+        };
+        if git_project.projects().iter().any(|project| project.task_with_id(&task_id).is_some()) {
+            return Ok(Some(task_id));
+        }
+    }
+    Ok(None)
+}
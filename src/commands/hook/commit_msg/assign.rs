@@ -0,0 +1,38 @@
+use std::error::Error;
+use regex::Regex;
+use crate::model::*;
+use super::common;
+
+pub fn assign(git_project: &mut GitProject, message: &str, branch_task: Option<&Id>, current_user: Option<&str>) -> Result<Vec<Transition>, Box<dyn Error>> {
+    let mut transitions = vec![];
+    let assign_command_format = Regex::new(r"\[(?:([^\[\]\s@+-][^\[\]\s@]*)(?:@([^\[\]\s]+))?[\s]+)?=([^\[\]\s]+)\]")?; // [my-task =me], [my-task@ios =alice@example.com], [=nobody]
+
+    for command in assign_command_format.captures_iter(message) {
+        let command_str = command.get(0).unwrap().as_str();
+        // without a task of its own or the branch's, this is more likely ordinary text in brackets
+        if command.get(1).is_none() && branch_task.is_none() { continue }
+        let task_id = common::resolve_task(command.get(1).map(|m| m.as_str()), branch_task, command_str)?;
+        let assignee = match command.get(3).map(|m| m.as_str()) {
+            Some("nobody") => None,
+            Some("me") => match current_user {
+                Some(current_user) => Some(current_user.to_string()),
+                None => {
+                    return common::fail(format!("user.email must be set to assign yourself, in command {}", command_str));
+                }
+            },
+            Some(assignee) => Some(assignee.to_string()),
+            None => continue, // shouldn't reach the continue here, as pattern should not match
+        };
+        let project = common::resolve_project(git_project, &task_id, None, false, command.get(2).map(|m| m.as_str()), command_str)?;
+
+        let mut task = if let Some(task) = project.task_with_id(&task_id) { task.clone() } else {
+            return common::fail(format!("No task was found with ID {} in project {}, referenced in command {}", task_id, project.id(), command_str));
+        };
+
+        transitions.push(Transition::Assign { project: project.id().clone(), task: task_id.clone(), from: task.assignee().map(str::to_string), to: assignee.clone() });
+        task.set_assignee(assignee);
+        project.replace_task(&task_id, task, None);
+    }
+
+    Ok(transitions)
+}
@@ -3,26 +3,81 @@ use regex::Regex;
 use crate::model::*;
 use super::common;
 
-pub fn change_column(git_project: &mut GitProject, message: &str) -> Result<(), Box<dyn Error>> {
-    let change_column_command_format = Regex::new(r"\[([^\[\]\s]+)(?:@([^\[\]\s]+))? is *([^\[\]\s]+)\]")?; // [my-task is done], [new-task@ios is in-progress]
+pub fn change_column(git_project: &mut GitProject, message: &str, branch_task: Option<&Id>) -> Result<Vec<Transition>, Box<dyn Error>> {
+    let mut transitions = vec![];
+    let change_column_command_format = Regex::new(r"\[(?:([^\[\]\s@]+)(?:@([^\[\]\s]+))? )?is\s+([^\[\]\s]+)\]")?; // [my-task is done], [new-task@ios is in-progress], [is done]
 
     for command in change_column_command_format.captures_iter(message) {
         let command_str = command.get(0).unwrap().as_str();
+        // without a task of its own or the branch's, this is more likely ordinary text in brackets
+        if command.get(1).is_none() && branch_task.is_none() { continue }
 
-        let task_id: Id = if let Some(task_id) = command.get(1) { task_id.as_str().into() } else { continue }; // shouldn't reach the continue here, as pattern should not match
-        let column_id: Id = if let Some(column_id) = command.get(3) { column_id.as_str().into() } else { continue }; // shouldn't reach the continue here either
-        let project = common::resolve_project(git_project, &task_id, Some(&column_id), command.get(2), command_str)?;
-        
-        if project.columns().iter().find(|column| column.id() == &column_id).is_none() {
-            eprintln!("git-project: No column was found with ID {} in project {}, referenced in command {}", column_id, project.id(), command_str);
-            std::process::exit(1);
-        }
-        if project.tasks().iter().find(|task| task.id() == &task_id).is_none() {
-            eprintln!("git-project: No task was found with ID {} in project {}, referenced in command {}", task_id, project.id(), command_str);
-            std::process::exit(1);
+        let task_id = common::resolve_task(command.get(1).map(|m| m.as_str()), branch_task, command_str)?;
+        let column_id: Id = if let Some(column_id) = command.get(3) { column_id.as_str().into() } else { continue }; // shouldn't reach the continue here
+        transitions.push(move_task(git_project, task_id, column_id, command.get(2).map(|m| m.as_str()), command_str)?);
+    }
+
+    Ok(transitions)
+}
+
+/// Moves a task to a column, failing the commit if either does not exist.
+pub fn move_task(git_project: &mut GitProject, task_id: Id, column_id: Id, project_id: Option<&str>, command_str: &str) -> Result<Transition, Box<dyn Error>> {
+    let project = common::resolve_project(git_project, &task_id, Some(&column_id), false, project_id, command_str)?;
+    
+    if project.columns().iter().find(|column| column.id() == &column_id).is_none() {
+        return common::fail(format!("No column was found with ID {} in project {}, referenced in command {}", column_id, project.id(), command_str));
+    }
+    let from = match project.task_with_id(&task_id) {
+        Some(task) => project.column_of_task(task).map(|column| column.id().clone()),
+        None => {
+            return common::fail(format!("No task was found with ID {} in project {}, referenced in command {}", task_id, project.id(), command_str));
         }
-        project.move_task_to_column(task_id.into(), column_id.into());
+    };
+    let transition = Transition::Column { project: project.id().clone(), task: task_id.clone(), from, to: column_id.clone() };
+    project.move_task_to_column(task_id, column_id);
+    Ok(transition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> GitProject {
+        let typo = Task::new("typo").name("Typo").description("").build().unwrap();
+        let project = Project::new("web")
+            .name("Web")
+            .description("")
+            .column(Column::new("todo").name("To do").description("").add_task(&typo).build().unwrap())
+            .column(Column::new("done").name("Done").description("").build().unwrap())
+            .task(typo)
+            .build()
+            .unwrap();
+        GitProject::new().project(project).build().unwrap()
+    }
+
+    fn moved(message: &str, branch_task: Option<&str>) -> Vec<String> {
+        let branch_task = branch_task.map(Id::new);
+        change_column(&mut board(), message, branch_task.as_ref()).unwrap()
+            .iter()
+            .map(|transition| transition.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn moves_the_task_named() {
+        assert_eq!(moved("Fix it [typo is done]", None), vec!["typo@web is done (was todo)"]);
+        assert_eq!(moved("Fix it [typo@web is done]", None), vec!["typo@web is done (was todo)"]);
+    }
+
+    #[test]
+    fn moves_the_branch_task() {
+        assert_eq!(moved("Fix it [is done]", Some("typo")), vec!["typo@web is done (was todo)"]);
     }
 
-    Ok(())
+    #[test]
+    fn ignores_ordinary_text_in_brackets() {
+        assert!(moved("Fix crash [issue-42]", None).is_empty());
+        assert!(moved("Fix crash [issue-42]", Some("typo")).is_empty());
+        assert!(moved("Fix it [is done]", None).is_empty());
+    }
 }
@@ -3,22 +3,24 @@ use regex::Regex;
 use crate::model::*;
 use super::common;
 
-pub fn change_tags(git_project: &mut GitProject, message: &str) -> Result<(), Box<dyn Error>> {
-    let change_tags_command_format = Regex::new(r"\[([^\[\]\s]+)(?:@([^\[\]\s]+))?((?:[\s]+(?:[+-][\S]+))+)\]")?; // [my-task +bug], [my-task@ios -blocked +important]
+pub fn change_tags(git_project: &mut GitProject, message: &str, branch_task: Option<&Id>) -> Result<Vec<Transition>, Box<dyn Error>> {
+    let mut transitions = vec![];
+    let change_tags_command_format = Regex::new(r"\[(?:([^\[\]\s@+-][^\[\]\s@]*)(?:@([^\[\]\s]+))?[\s]+)?((?:[+-][^\[\]\s]+)(?:[\s]+[+-][^\[\]\s]+)*)\]")?; // [my-task +bug], [my-task@ios -blocked +important], [+bug]
 
     for command in change_tags_command_format.captures_iter(message) {
         let command_str = command.get(0).unwrap().as_str();
-        let task_id: Id = if let Some(task_id) = command.get(1) { task_id.as_str().into() } else { continue }; // shouldn't reach the continue here, as pattern should not match
+        // without a task of its own or the branch's, this is more likely ordinary text in brackets
+        if command.get(1).is_none() && branch_task.is_none() { continue }
+        let task_id = common::resolve_task(command.get(1).map(|m| m.as_str()), branch_task, command_str)?;
         let tags: &str = if let Some(tags) = command.get(3) { tags.as_str() } else { continue };
-        let project = common::resolve_project(git_project, &task_id, None, command.get(2), command_str)?;
+        let project = common::resolve_project(git_project, &task_id, None, false, command.get(2).map(|m| m.as_str()), command_str)?;
 
         let mut task = if let Some(task) = project.task_with_id(&task_id) { task.clone() } else {
-            eprintln!("No task was found with ID {} in project {}, referenced in command {}", task_id, project.id(), command_str);
-            std::process::exit(1);
+            return common::fail(format!("No task was found with ID {} in project {}, referenced in command {}", task_id, project.id(), command_str));
         };
 
+        let before = task.tags().to_vec();
         for tag in tags.split(|ch: char| ch.is_whitespace()).filter(|s| !s.is_empty()) {
-            eprintln!("match: {:?}", tag);
             match tag.split_at(1) {
                 ("+", tag) => task.add_tag(tag),
                 ("-", tag) => task.remove_tag(tag),
@@ -26,8 +28,53 @@ pub fn change_tags(git_project: &mut GitProject, message: &str) -> Result<(), Bo
             }
         }
 
+        // only the tags that actually changed are recorded, so that undoing the change (e.g. when
+        // the commit is reverted) leaves the tags the task already had alone
+        let added: Vec<String> = task.tags().iter().filter(|tag| !before.contains(tag)).cloned().collect();
+        let removed: Vec<String> = before.iter().filter(|tag| !task.tags().contains(tag)).cloned().collect();
+        if !added.is_empty() || !removed.is_empty() {
+            transitions.push(Transition::Tags { project: project.id().clone(), task: task_id.clone(), added, removed });
+        }
         project.replace_task(&task_id, task, None);
     }
 
-    Ok(())
+    Ok(transitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> GitProject {
+        let typo = Task::new("typo").name("Typo").description("").tag("bug").build().unwrap();
+        let project = Project::new("web")
+            .name("Web")
+            .description("")
+            .column(Column::new("todo").name("To do").description("").add_task(&typo).build().unwrap())
+            .task(typo)
+            .build()
+            .unwrap();
+        GitProject::new().project(project).build().unwrap()
+    }
+
+    fn tagged(message: &str, branch_task: Option<&str>) -> Vec<String> {
+        let branch_task = branch_task.map(Id::new);
+        change_tags(&mut board(), message, branch_task.as_ref()).unwrap()
+            .iter()
+            .map(|transition| transition.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn records_only_the_tags_that_changed() {
+        assert_eq!(tagged("Fix it [typo +bug +ui -wip]", None), vec!["typo@web +ui"]);
+        assert_eq!(tagged("Fix it [-bug]", Some("typo")), vec!["typo@web -bug"]);
+        assert!(tagged("Fix it [typo +bug]", None).is_empty());
+    }
+
+    #[test]
+    fn ignores_ordinary_text_in_brackets() {
+        assert!(tagged("Add [-v] flag", None).is_empty());
+        assert!(tagged("Bump the count [+1]", None).is_empty());
+    }
 }
@@ -0,0 +1,136 @@
+use std::error::Error;
+use regex::Regex;
+use crate::model::*;
+use super::{common, change_column};
+use super::super::message;
+
+const CLOSE_COLUMN_CONFIG_KEY: &str = "project.closeColumn";
+const CLOSING_KEYWORDS: &str = "close|closes|closed|fix|fixes|fixed|resolve|resolves|resolved";
+
+/// Applies the commands given as trailers (`Task: my-task done`, `Closes: my-task`) and closing
+/// keywords in the message body (`fixes my-task`). Closed tasks are moved to the column configured
+/// in `project.closeColumn`, or to the last column of their project.
+pub fn close_tasks(git_project: &mut GitProject, message: &str, branch_task: Option<&Id>, close_column: Option<&str>) -> Result<Vec<Transition>, Box<dyn Error>> {
+    let mut transitions = vec![];
+    let (body, trailers) = message::split_trailers(message);
+
+    let closing_trailer_format = Regex::new(&format!(r"(?i)^(?:{})$", CLOSING_KEYWORDS))?;
+    for (key, value) in &trailers {
+        let command_str = format!("{}: {}", key, value);
+        if key.eq_ignore_ascii_case("task") {
+            // Task: my-task done, Task: my-task@ios done, Task: done
+            let words: Vec<&str> = value.split_whitespace().collect();
+            let (task, column_id) = match words.as_slice() {
+                [column_id] => (None, *column_id),
+                [task, column_id] => (Some(*task), *column_id),
+                _ => {
+                    return common::fail(format!("Trailer {} should be of the form Task: <task> <column>", command_str));
+                }
+            };
+            let mut parts = task.map(|task| task.splitn(2, '@'));
+            let task_id = common::resolve_task(parts.as_mut().and_then(Iterator::next), branch_task, &command_str)?;
+            let project_id = parts.as_mut().and_then(Iterator::next);
+            transitions.push(change_column::move_task(git_project, task_id, column_id.into(), project_id, &command_str)?);
+        } else if closing_trailer_format.is_match(key) {
+            for task in value.split(|ch: char| ch.is_whitespace() || ch == ',').filter(|task| !task.is_empty()) {
+                transitions.extend(close_task(git_project, task.trim_start_matches('#'), close_column, &command_str)?);
+            }
+        }
+    }
+
+    // the type of a conventional commit subject (`fix: typo`, `fix(ui)!: typo`) is not a closing
+    // keyword, and keywords only close the task they are directly followed by
+    let subject_type_format = Regex::new(r"^[[:alpha:]]+(?:\([^()\n]*\))?!?:\s")?;
+    let body = subject_type_format.find(body).map(|subject_type| &body[subject_type.end()..]).unwrap_or(body);
+    let closing_keyword_format = Regex::new(&format!(r"(?i)\b(?:{}):?[ \t]+#?([^\[\]\s,;]+)", CLOSING_KEYWORDS))?;
+    for command in closing_keyword_format.captures_iter(body) {
+        let task = command[1].trim_end_matches(|ch: char| ch.is_ascii_punctuation());
+        transitions.extend(close_task(git_project, task, close_column, &command[0])?);
+    }
+
+    Ok(transitions)
+}
+
+/// Closes a task mentioned in passing. These may well not refer to a task at all (e.g. "fixes the
+/// bug"), so tasks which cannot be found are skipped rather than failing the commit.
+fn close_task(git_project: &mut GitProject, task: &str, close_column: Option<&str>, command_str: &str) -> Result<Option<Transition>, Box<dyn Error>> {
+    let mut parts = task.splitn(2, '@');
+    let task_id: Id = parts.next().unwrap_or_default().into();
+    let project_id = parts.next();
+
+    let projects: Vec<&Project> = git_project.projects().iter()
+        .filter(|project| project_id.map(|id| project.id().as_ref() == id).unwrap_or(true))
+        .filter(|project| project.task_with_id(&task_id).is_some())
+        .collect();
+    let project = match projects.as_slice() {
+        [project] => project,
+        [] => return Ok(None),
+        _ => {
+            eprintln!("git-project: Ambiguous task with ID {} in {} was not closed. Specify a project ID to disambiguate, e.g. {}@{}", task_id, command_str, task_id, projects[0].id());
+            return Ok(None);
+        }
+    };
+    let column_id = match close_column.and_then(|id| project.columns().iter().find(|column| column.id().as_ref() == id)).or_else(|| project.columns().last()) {
+        Some(column) => column.id().clone(),
+        None => return Ok(None),
+    };
+    let project_id = project.id().to_string();
+    Ok(Some(change_column::move_task(git_project, task_id, column_id, Some(&project_id), command_str)?))
+}
+
+/// The column that closed tasks are moved to, if one has been configured.
+pub fn configured_close_column(config: &git2::Config) -> Option<String> {
+    config.get_string(CLOSE_COLUMN_CONFIG_KEY).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> GitProject {
+        let typo = Task::new("typo").name("Typo").description("").build().unwrap();
+        let login = Task::new("login-form").name("Login form").description("").build().unwrap();
+        let project = Project::new("web")
+            .name("Web")
+            .description("")
+            .column(Column::new("todo").name("To do").description("").add_task(&typo).add_task(&login).build().unwrap())
+            .column(Column::new("done").name("Done").description("").build().unwrap())
+            .task(typo)
+            .task(login)
+            .build()
+            .unwrap();
+        GitProject::new().project(project).build().unwrap()
+    }
+
+    fn closed(message: &str) -> Vec<String> {
+        let mut git_project = board();
+        close_tasks(&mut git_project, message, None, None).unwrap()
+            .iter()
+            .map(|transition| transition.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn closes_tasks_after_keywords() {
+        assert_eq!(closed("Add the form\n\nThis fixes #login-form."), vec!["login-form@web is done (was todo)"]);
+        assert_eq!(closed("Add the form\n\nResolves: login-form"), vec!["login-form@web is done (was todo)"]);
+    }
+
+    #[test]
+    fn closes_tasks_in_trailers() {
+        assert_eq!(closed("Add the form\n\nCloses: login-form, #typo"), vec!["login-form@web is done (was todo)", "typo@web is done (was todo)"]);
+    }
+
+    #[test]
+    fn ignores_conventional_commit_types() {
+        assert!(closed("fix: typo in the readme").is_empty());
+        assert!(closed("fix(docs)!: typo in the readme").is_empty());
+        assert_eq!(closed("fix: closes typo"), vec!["typo@web is done (was todo)"]);
+    }
+
+    #[test]
+    fn ignores_keywords_not_followed_by_a_task() {
+        assert!(closed("Tidy up\n\nThis fixes the\nlogin-form.").is_empty());
+        assert!(closed("Tidy up\n\nFixed a typo").is_empty());
+    }
+}
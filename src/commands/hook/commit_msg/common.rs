@@ -1,15 +1,39 @@
 use std::error::Error;
-use regex::Match;
+use std::fmt::{self, Display, Formatter};
 use crate::model::*;
 
-pub fn resolve_project<'a>(git_project: &'a mut GitProject, task_id: &Id, column_id: Option<&Id>, project_match: Option<Match>, command_str: &str) -> Result<&'a mut Project, Box<dyn Error>> {
-    let project: &mut Project = match project_match.map(|m| m.as_str()) {
+/// A command in a commit message that cannot be applied, which fails the commit.
+#[derive(Debug)]
+pub struct CommandError(String);
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "git-project: {}", self.0)
+    }
+}
+impl Error for CommandError {}
+
+pub fn fail<T>(message: String) -> Result<T, Box<dyn Error>> {
+    Err(Box::new(CommandError(message)))
+}
+
+/// The task referenced by a command, which is the current branch's task if the command did not name one.
+pub fn resolve_task(task_id: Option<&str>, branch_task: Option<&Id>, command_str: &str) -> Result<Id, Box<dyn Error>> {
+    match (task_id, branch_task) {
+        (Some(task_id), _) => Ok(task_id.into()),
+        (None, Some(task_id)) => Ok(task_id.clone()),
+        (None, None) => fail(format!("No task ID was given in command {}, and none could be inferred from the branch name", command_str)),
+    }
+}
+
+/// Finds the project a command refers to: the one named in the command, or otherwise the only one
+/// with the task (or without it, for commands creating a new task) and column in question.
+pub fn resolve_project<'a>(git_project: &'a mut GitProject, task_id: &Id, column_id: Option<&Id>, new_task: bool, project_id: Option<&str>, command_str: &str) -> Result<&'a mut Project, Box<dyn Error>> {
+    let project: &mut Project = match project_id {
         Some(project_id) => {
             if let Some(project) = git_project.projects_mut().iter_mut().find(|project| project.id().as_ref() == project_id) {
                 project
             } else {
-                eprintln!("git-project: No project was found with ID {}, used in command {}", project_id, command_str);
-                std::process::exit(1);
+                return fail(format!("No project was found with ID {}, used in command {}", project_id, command_str));
             }
         }
         None => {
@@ -17,23 +41,29 @@ pub fn resolve_project<'a>(git_project: &'a mut GitProject, task_id: &Id, column
                 .projects_mut()
                 .iter_mut()
                 .filter(|project|
-                    project.tasks().iter().find(|task| task.id() == task_id).is_some()
+                    project.tasks().iter().find(|task| task.id() == task_id).is_some() != new_task
                     && (column_id.is_none()
                         || project.columns().iter().find(|column| column.id() == column_id.unwrap()).is_some())
                 )
                 .collect();
+            if matching_projects.is_empty() && new_task {
+                return fail(format!("No project was found with a column with ID {}, referenced in command {}", column_id.unwrap(), command_str));
+            }
             if matching_projects.is_empty() {
-                eprintln!("git-project: No task was found with ID {} in any project, referenced in command {}", task_id, command_str);
-                std::process::exit(1);
+                return fail(format!("No task was found with ID {} in any project, referenced in command {}", task_id, command_str));
             }
             if matching_projects.len() > 1 {
-                eprintln!("git-project: Ambiguous task with ID {} is present in these projects:", task_id);
+                let mut message = if new_task {
+                    "Ambiguous command could create a task in these projects:".to_string()
+                } else {
+                    format!("Ambiguous task with ID {} is present in these projects:", task_id)
+                };
                 for project in matching_projects.iter() {
-                    eprintln!("             *   {} ({})", project.name(), project.id());
+                    message += &format!("\n             *   {} ({})", project.name(), project.id());
                 }
-                eprintln!("             Referenced in command {}", command_str);
-                eprintln!("             Specify a project ID to disambiguate, e.g. {}@{}", task_id, matching_projects.first().unwrap().id());
-                std::process::exit(1);
+                message += &format!("\n             Referenced in command {}", command_str);
+                message += &format!("\n             Specify a project ID to disambiguate, e.g. {}@{}", task_id, matching_projects.first().unwrap().id());
+                return fail(message);
             }
             matching_projects.into_iter().next().unwrap()
         }
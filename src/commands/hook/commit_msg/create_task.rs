@@ -0,0 +1,34 @@
+use std::error::Error;
+use regex::Regex;
+use crate::model::*;
+use super::common;
+
+pub fn create_task(git_project: &mut GitProject, message: &str) -> Result<Vec<Transition>, Box<dyn Error>> {
+    let mut transitions = vec![];
+    let create_task_command_format = Regex::new(r#"\[\+([^\[\]\s@"]*)(?:@([^\[\]\s"]+))?[\s]+"([^"]+)"[\s]+in[\s]+([^\[\]\s]+)\]"#)?; // [+new-task "Title" in backlog], [+new-task@ios "Title" in backlog], [+ "Title" in backlog]
+
+    for command in create_task_command_format.captures_iter(message) {
+        let command_str = command.get(0).unwrap().as_str();
+        let task_id: Id = command.get(1).map(|m| m.as_str()).unwrap_or_default().into();
+        let name = if let Some(name) = command.get(3) { name.as_str().trim() } else { continue }; // shouldn't reach the continue here, as pattern should not match
+        let column_id: Id = if let Some(column_id) = command.get(4) { column_id.as_str().into() } else { continue }; // shouldn't reach the continue here either
+        let project = common::resolve_project(git_project, &task_id, Some(&column_id), true, command.get(2).map(|m| m.as_str()), command_str)?;
+
+        if project.task_with_id(&task_id).is_some() {
+            return common::fail(format!("A task with ID {} already exists in project {}, referenced in command {}", task_id, project.id(), command_str));
+        }
+        let column = match project.columns().iter().position(|column| column.id() == &column_id) {
+            Some(column) => column,
+            None => {
+                return common::fail(format!("No column was found with ID {} in project {}, referenced in command {}", column_id, project.id(), command_str));
+            }
+        };
+        let task_id = if task_id.as_ref().is_empty() { project.generate_task_id(name) } else { task_id };
+        let task = Task::new(&task_id).name(name).description("").build().unwrap();
+
+        transitions.push(Transition::Create { project: project.id().clone(), task: task_id, name: name.to_string(), column: column_id });
+        project.add_task(task, column);
+    }
+
+    Ok(transitions)
+}
@@ -1,13 +1,17 @@
 use std::error::Error;
 use std::fs::read_to_string;
 use std::path::PathBuf;
+use git2::Repository;
 use structopt::StructOpt;
 use crate::model::*;
-use crate::PROJECT_TEMP_FILE;
+use super::{branch, message, state::HookState};
 
 mod common;
 mod change_column;
 mod change_tags;
+mod close_tasks;
+mod assign;
+mod create_task;
 
 #[derive(StructOpt, Debug)]
 pub struct CommitMsg {
@@ -15,12 +19,37 @@ pub struct CommitMsg {
 }
 
 pub fn commit_msg(args: CommitMsg) -> Result<(), Box<dyn Error>> {
-    if !PathBuf::from(PROJECT_TEMP_FILE).exists() { return Ok(()); }
-    let mut git_project = GitProject::open()?;
+    let repository = Repository::open_from_env()?;
+    let mut state = match HookState::take(&repository)? {
+        Some(state) if state.started_at_head(&repository) => state,
+        _ => return Ok(()),
+    };
     let message = read_to_string(&args.file)?;
+    let mut git_project = GitProject::open()?;
+    let transitions = apply_commands(&repository, &mut git_project, &message)?;
+
+    // the commit may still be aborted, so the board is only changed once it has been made, during
+    // post-commit
+    state.complete(&std::env::current_dir()?.join(&args.file), &message, git_project, transitions);
+    state.save(&repository)
+}
+
+/// Applies the commands in a commit message to a project, without saving it, returning the
+/// resulting transitions. The message is cleaned up first, the same way Git would.
+pub fn apply_commands(repository: &Repository, git_project: &mut GitProject, raw_message: &str) -> Result<Vec<Transition>, Box<dyn Error>> {
+    let config = repository.config()?.snapshot()?;
+    let message = message::clean(raw_message, message::comment_char(&config, raw_message), message::Cleanup::configured(&config));
+
+    let current_user = config.get_string("user.email").ok();
 
-    change_column::change_column(&mut git_project, message.as_str())?;
-    change_tags::change_tags(&mut git_project, message.as_str())?;
+    // tasks are created first, so that the other commands in the message can refer to them
+    let mut transitions = create_task::create_task(git_project, message.as_str())?;
+    let branch_task = branch::branch_task(repository, git_project)?;
+    transitions.extend(change_column::change_column(git_project, message.as_str(), branch_task.as_ref())?);
+    transitions.extend(change_tags::change_tags(git_project, message.as_str(), branch_task.as_ref())?);
+    transitions.extend(assign::assign(git_project, message.as_str(), branch_task.as_ref(), current_user.as_deref())?);
+    let close_column = close_tasks::configured_close_column(&config);
+    transitions.extend(close_tasks::close_tasks(git_project, message.as_str(), branch_task.as_ref(), close_column.as_deref())?);
 
-    git_project.save()
+    Ok(transitions)
 }
@@ -0,0 +1,183 @@
+use git2::Config;
+use regex::Regex;
+
+/// The characters Git chooses from when `core.commentChar` is `auto`.
+const AUTO_COMMENT_CHARS: &str = "#;@!$%^&|:";
+/// The marker of the line below which `git commit --verbose` places the diff.
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// How Git cleans up a commit message before committing it, as set by `commit.cleanup`.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Cleanup {
+    /// Strip comments, and everything below the scissors line.
+    Strip,
+    /// Keep comments, but remove everything below the scissors line.
+    Scissors,
+    /// Keep comments and the scissors line, and only tidy up whitespace.
+    Whitespace,
+    /// Leave the message as is.
+    Verbatim,
+}
+
+impl Cleanup {
+    /// The cleanup mode configured for the repository. The `--cleanup` option of `git commit` is not
+    /// visible to hooks, and neither is whether an editor was used, so the `default` mode is taken to
+    /// be `strip`, as it is for messages written in an editor.
+    pub fn configured(config: &Config) -> Cleanup {
+        match config.get_str("commit.cleanup").unwrap_or("default") {
+            "verbatim" => Cleanup::Verbatim,
+            "whitespace" => Cleanup::Whitespace,
+            "scissors" => Cleanup::Scissors,
+            _ => Cleanup::Strip,
+        }
+    }
+}
+
+/// The character Git uses to start comment lines in commit messages. When `core.commentChar` is
+/// `auto`, Git picks a character that no line of the message started with, so it is found by
+/// looking for the comment lines that Git added to the message.
+pub fn comment_char(config: &Config, message: &str) -> char {
+    match config.get_str("core.commentChar") {
+        Ok("auto") => AUTO_COMMENT_CHARS.chars()
+            .find(|ch| message.lines().any(|line| line.starts_with(*ch) && (line[ch.len_utf8()..].starts_with(' ') || line.len() == ch.len_utf8())))
+            .unwrap_or('#'),
+        Ok(value) => value.chars().next().unwrap_or('#'),
+        Err(..) => '#',
+    }
+}
+
+fn scissors_line(comment_char: char) -> String {
+    format!("{} {}", comment_char, SCISSORS)
+}
+
+/// Where the scissors line starts in a message, if it has one. Git throws away everything from
+/// there on, however the message is cleaned up.
+pub fn scissors_position(message: &str, comment_char: char) -> Option<usize> {
+    let scissors = scissors_line(comment_char);
+    let mut position = 0;
+    for line in message.split_inclusive('\n') {
+        if line.trim_end_matches(&['\n', '\r'][..]) == scissors { return Some(position) }
+        position += line.len();
+    }
+    None
+}
+
+/// Cleans up a commit message the way Git will before committing it, so that commands are only
+/// taken from the parts of the message that will actually be committed.
+pub fn clean(message: &str, comment_char: char, cleanup: Cleanup) -> String {
+    if cleanup == Cleanup::Verbatim { return message.to_string() }
+    let scissors = scissors_line(comment_char);
+    let lines = message.lines()
+        .take_while(|line| cleanup == Cleanup::Whitespace || *line != scissors)
+        .filter(|line| cleanup != Cleanup::Strip || !line.starts_with(comment_char));
+    strip_whitespace(lines)
+}
+
+/// Removes trailing whitespace from lines, and blank lines from the start and end of a message,
+/// and collapses runs of blank lines into one, as `git stripspace` does.
+fn strip_whitespace<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let mut cleaned = String::new();
+    let mut blank = false;
+    for line in lines.map(str::trim_end) {
+        if line.is_empty() {
+            blank = !cleaned.is_empty();
+            continue;
+        }
+        if blank { cleaned.push('\n'); }
+        blank = false;
+        cleaned.push_str(line);
+        cleaned.push('\n');
+    }
+    cleaned
+}
+
+/// Splits the trailers off the end of a commit message, following the conventions of
+/// `git interpret-trailers`: trailers are `Key: value` lines, possibly continued on indented
+/// lines, which make up the entire last paragraph of a message that has a body.
+pub fn split_trailers(message: &str) -> (&str, Vec<(String, String)>) {
+    let trailer_format = Regex::new(r"^([A-Za-z0-9-]+)\s*:\s*(.*)$").unwrap();
+
+    let message = message.trim_end();
+    let last_paragraph = match message.rfind("\n\n") {
+        Some(index) => index + 2,
+        None => return (message, vec![]),
+    };
+
+    let mut trailers: Vec<(String, String)> = vec![];
+    for line in message[last_paragraph..].lines() {
+        if line.starts_with(char::is_whitespace) {
+            match trailers.last_mut() {
+                Some((_, value)) => {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                None => return (message, vec![]),
+            }
+        } else if let Some(captures) = trailer_format.captures(line) {
+            trailers.push((captures[1].to_string(), captures[2].trim().to_string()));
+        } else {
+            return (message, vec![]);
+        }
+    }
+    (&message[..last_paragraph], trailers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &str = "\n\nSubject  \n\n\n\nBody\t\n# a comment\n\n# ------------------------ >8 ------------------------\n[task is done]\n";
+
+    #[test]
+    fn strip_removes_comments_and_the_diff() {
+        assert_eq!(clean(MESSAGE, '#', Cleanup::Strip), "Subject\n\nBody\n");
+    }
+
+    #[test]
+    fn scissors_keeps_comments_but_removes_the_diff() {
+        assert_eq!(clean(MESSAGE, '#', Cleanup::Scissors), "Subject\n\nBody\n# a comment\n");
+    }
+
+    #[test]
+    fn whitespace_keeps_comments_and_the_diff() {
+        assert_eq!(
+            clean(MESSAGE, '#', Cleanup::Whitespace),
+            "Subject\n\nBody\n# a comment\n\n# ------------------------ >8 ------------------------\n[task is done]\n",
+        );
+    }
+
+    #[test]
+    fn verbatim_keeps_everything() {
+        assert_eq!(clean(MESSAGE, '#', Cleanup::Verbatim), MESSAGE);
+    }
+
+    #[test]
+    fn finds_the_scissors_line() {
+        let position = scissors_position(MESSAGE, '#').unwrap();
+        assert!(MESSAGE[position..].starts_with("# ------------------------ >8"));
+        assert_eq!(scissors_position(MESSAGE, ';'), None);
+        assert_eq!(scissors_position("Subject\n# ------------------------ >8 ------------------------ not quite\n", '#'), None);
+    }
+
+    #[test]
+    fn other_comment_chars() {
+        assert_eq!(clean("Subject\n; comment\n# kept\n", ';', Cleanup::Strip), "Subject\n# kept\n");
+    }
+
+    #[test]
+    fn trailers_are_split_off_the_last_paragraph() {
+        let (body, trailers) = split_trailers("Subject\n\nBody\n\nCloses: a-task\nTask: b-task done\n  and more\n");
+        assert_eq!(body, "Subject\n\nBody\n\n");
+        assert_eq!(trailers, vec![
+            ("Closes".to_string(), "a-task".to_string()),
+            ("Task".to_string(), "b-task done and more".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn trailers_need_a_body_and_a_whole_paragraph() {
+        assert_eq!(split_trailers("Closes: a-task\n"), ("Closes: a-task", vec![]));
+        assert_eq!(split_trailers("Subject\n\nCloses: a-task\nnot a trailer\n").1, vec![]);
+        assert_eq!(split_trailers("Subject\n\n  Closes: a-task\n").1, vec![]);
+    }
+}
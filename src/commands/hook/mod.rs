@@ -5,11 +5,18 @@ mod pre_commit;
 mod commit_msg;
 mod prepare_commit_msg;
 mod post_commit;
+mod post_rewrite;
+mod pre_receive;
+mod message;
+mod branch;
+mod state;
 
 pub use pre_commit::*;
 pub use commit_msg::*;
 pub use prepare_commit_msg::*;
 pub use post_commit::*;
+pub use post_rewrite::*;
+pub use pre_receive::*;
 
 #[derive(StructOpt, Debug)]
 pub enum Hook {
@@ -25,6 +32,17 @@ pub enum Hook {
     /// The post-commit hook. Call this during the post-commit Git hook, along with the
     /// matching pre-commit to enable automatically committing the changes to .gitproject
     PostCommit,
+    /// The post-rewrite hook. Call this during the post-rewrite Git hook to keep the board changes
+    /// recorded for commits when they are rebased.
+    PostRewrite(PostRewrite),
+    /// The pre-receive hook, for repositories that are pushed to. Call this during the
+    /// pre-receive Git hook to reject pushes that would break the project board, such as those
+    /// with tasks in unknown or duplicate columns, or that move tasks out of the columns listed in
+    /// the `project.lockedColumn` config.
+    PreReceive,
+    /// The update hook. The same as pre-receive, but checks a single ref at a time, so that other
+    /// refs in the same push may still be accepted.
+    Update(Update),
 }
 
 pub fn hook(args: Hook) -> Result<(), Box<dyn Error>> {
@@ -33,5 +51,8 @@ pub fn hook(args: Hook) -> Result<(), Box<dyn Error>> {
         Hook::CommitMsg(args) => commit_msg(args),
         Hook::PrepareCommitMsg(args) => prepare_commit_msg(args),
         Hook::PostCommit => post_commit(),
+        Hook::PostRewrite(args) => post_rewrite(args),
+        Hook::PreReceive => pre_receive(),
+        Hook::Update(args) => update(args),
     }
 }
@@ -1,17 +1,188 @@
+use std::env;
 use std::error::Error;
-use std::fs::remove_file;
-use std::path::PathBuf;
-use std::process::Command;
-use crate::PROJECT_TEMP_FILE;
-use crate::PROJECT_FILE_NAME;
+use std::fmt::{self, Display, Formatter};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use git2::{Commit, Config, Index, Oid, Repository, RepositoryState};
+use regex::Regex;
+use crate::model::{GitProject, Transition};
+use crate::commands::{record_transitions, recorded_transitions};
+use super::state::HookState;
+
+#[derive(Debug)]
+struct SigningError(String);
+impl Display for SigningError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "git-project: Failed to sign the amended commit: {}", self.0)
+    }
+}
+impl Error for SigningError {}
 
-// TODO: would be nice to use the git library for this, but it would be much more complicated
 pub fn post_commit() -> Result<(), Box<dyn Error>> {
-    let path = PathBuf::from(PROJECT_TEMP_FILE);
-    if path.exists() {
-        remove_file(&path)?;
-        Command::new("git").arg("add").arg(PROJECT_FILE_NAME).spawn()?;
-        Command::new("git").arg("commit").arg("--amend").arg("--no-verify").arg("--quiet").spawn()?;
+    let repository = Repository::open_from_env()?;
+    let state = HookState::take(&repository)?;
+    let head = repository.head()?.peel_to_commit()?;
+    let changes = match state {
+        Some(state) if state.completed_for(&repository, &head) => state.into_changes(),
+        _ => None,
+    };
+
+    if changes.is_none() {
+        // the commits picked by a rebase are left to the post-rewrite hook, which runs once the
+        // rebase is done
+        if let RepositoryState::Rebase | RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge = repository.state() {
+            return Ok(());
+        }
+        // cherry-picks carry the board changes of the original commit along with the rest of its
+        // tree, so those changes are not applied again, only recorded
+        if let Some(picked) = picked_commit(&repository, &head) {
+            return copy_transitions(&repository, picked, head.id());
+        }
+    }
+    update_board(&repository, changes)
+}
+
+/// The commit that HEAD was cherry-picked from: the one being picked, if the pick stopped to resolve
+/// conflicts, or otherwise the one named by `git cherry-pick -x`.
+fn picked_commit(repository: &Repository, head: &Commit) -> Option<Oid> {
+    if let Ok(picked) = repository.revparse_single("CHERRY_PICK_HEAD") {
+        return Some(picked.id());
+    }
+    let picked_format = Regex::new(r"(?m)^\(cherry picked from commit ([0-9a-f]{40})\)$").unwrap();
+    let picked = picked_format.captures_iter(head.message().unwrap_or_default()).last()?;
+    Oid::from_str(&picked[1]).ok()
+}
+
+/// Records the transitions of a commit against a copy of it made by a cherry-pick or rebase, unless
+/// the copy already has some of its own.
+pub fn copy_transitions(repository: &Repository, original: Oid, copy: Oid) -> Result<(), Box<dyn Error>> {
+    if !recorded_transitions(repository, copy)?.is_empty() { return Ok(()) }
+    record_transitions(repository, copy, &recorded_transitions(repository, original)?)
+}
+
+/// Saves the board resulting from the commands in the commit just made, and applies the inverse of
+/// the transitions recorded for each commit it reverts, then amends the project file into the commit
+/// and records the transitions.
+///
+/// `changes` are the board and transitions from commands in the message, if the commit ran the
+/// commit-msg hook. It does not for `git revert`, so reverts are handled here rather than alongside
+/// the other commands. The reverted changes to the project file are usually undone by the revert
+/// already, but applying the inverse transitions also covers the cases where Git could not revert
+/// them cleanly.
+fn update_board(repository: &Repository, changes: Option<(GitProject, Vec<Transition>)>) -> Result<(), Box<dyn Error>> {
+    let reverts_format = Regex::new(r"(?m)^This reverts commit ([0-9a-f]{4,40})\b").unwrap();
+    let head = repository.head()?.peel_to_commit()?;
+
+    let mut inverse = vec![];
+    for reverted in reverts_format.captures_iter(head.message().unwrap_or_default()) {
+        let reverted = match repository.revparse_single(&reverted[1]) {
+            Ok(reverted) => reverted.id(),
+            Err(_) => continue,
+        };
+        inverse.extend(recorded_transitions(repository, reverted)?
+            .iter()
+            .rev()
+            .filter_map(Transition::inverse));
+    }
+    if changes.is_none() && inverse.is_empty() { return Ok(()) }
+
+    let mut git_project = GitProject::open()?;
+    let before = git_project.clone();
+    let mut transitions = vec![];
+    if let Some((board, committed)) = changes {
+        git_project.restore(board);
+        transitions = committed;
+    }
+    for transition in &inverse {
+        transition.apply(&mut git_project);
+    }
+    transitions.extend(inverse);
+    if git_project.projects() != before.projects() {
+        git_project.save()?;
+    }
+
+    let head = amend_with_project(repository)?;
+    record_transitions(repository, head, &transitions)
+}
+
+/// Amends the project file into the HEAD commit, keeping everything else about the commit as it
+/// was, and returns the ID of the resulting commit. If the project file is unchanged, HEAD is left
+/// as it is.
+fn amend_with_project(repository: &Repository) -> Result<Oid, Box<dyn Error>> {
+    let (root, _) = GitProject::locate()?;
+    let file_name = root.file_name().unwrap().to_str().unwrap();
+    let head = repository.head()?.peel_to_commit()?;
+
+    // the project file always lives at the root of the working tree
+    let blob = repository.blob_path(&root)?;
+    let mut builder = repository.treebuilder(Some(&head.tree()?))?;
+    builder.insert(file_name, blob, 0o100644)?;
+    let tree = repository.find_tree(builder.write()?)?;
+
+    // stage the file too, so it is not left showing as modified
+    if let Some(index_file) = env::var_os("GIT_INDEX_FILE") {
+        repository.set_index(&mut Index::open(Path::new(&index_file))?)?;
+    }
+    let mut index = repository.index()?;
+    index.add_path(root.strip_prefix(repository.workdir().unwrap())?)?;
+    index.write()?;
+
+    if tree.id() == head.tree_id() { return Ok(head.id()) }
+
+    // the amended commit is signed if the original was, whatever commit.gpgSign says, as it may
+    // have been made with --gpg-sign or --no-gpg-sign
+    let original_signature = match repository.extract_signature(&head.id(), None) {
+        Ok((signature, _)) => signature,
+        Err(_) => return Ok(head.amend(Some("HEAD"), None, None, None, None, Some(&tree))?),
+    };
+
+    let config = repository.config()?.snapshot()?;
+    let parents: Vec<Commit> = head.parents().collect();
+    let parents: Vec<&Commit> = parents.iter().collect();
+    let buffer = repository.commit_create_buffer(&head.author(), &head.committer(), head.message_raw().unwrap_or_default(), &tree, &parents)?;
+    let buffer = buffer.as_str().ok_or_else(|| SigningError("the commit is not valid UTF-8".to_string()))?;
+    let signature = sign(&config, &head, &original_signature, buffer)?;
+    let amended = repository.commit_signed(buffer, &signature, None)?;
+    let summary = head.summary().unwrap_or_default();
+    repository.find_reference("HEAD")?.resolve()?.set_target(amended, &format!("commit (amend): {}", summary))?;
+    Ok(amended)
+}
+
+/// Signs a commit buffer the way Git would, according to the `gpg.*` and `user.signingKey` config,
+/// in the same format as the signature of the commit being amended.
+fn sign(config: &Config, head: &Commit, original_signature: &[u8], buffer: &str) -> Result<String, Box<dyn Error>> {
+    let format = match original_signature {
+        signature if signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") => "ssh".to_string(),
+        signature if signature.starts_with(b"-----BEGIN SIGNED MESSAGE-----") => "x509".to_string(),
+        signature if signature.starts_with(b"-----BEGIN PGP SIGNATURE-----") => "openpgp".to_string(),
+        _ => config.get_string("gpg.format").unwrap_or_else(|_| "openpgp".to_string()),
+    };
+    let signing_key = config.get_string("user.signingKey").ok();
+    let program = |key: &str, default: &str| config.get_string(key).unwrap_or_else(|_| default.to_string());
+
+    let mut command = match format.as_str() {
+        "ssh" => {
+            let signing_key = signing_key.ok_or_else(|| SigningError("user.signingKey must be set to sign with SSH".to_string()))?;
+            let mut command = Command::new(program("gpg.ssh.program", "ssh-keygen"));
+            command.args(["-Y", "sign", "-n", "git", "-f", &signing_key]);
+            command
+        }
+        format => {
+            let default_program = if format == "x509" { "gpgsm" } else { config.get_str("gpg.program").unwrap_or("gpg") };
+            let committer = head.committer();
+            let signing_key = signing_key.unwrap_or_else(|| format!("{} <{}>", committer.name().unwrap_or_default(), committer.email().unwrap_or_default()));
+            let mut command = Command::new(program(&format!("gpg.{}.program", format), default_program));
+            command.args(["--status-fd=2", "-bsau", &signing_key]);
+            command
+        }
+    };
+
+    let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+    child.stdin.take().unwrap().write_all(buffer.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Box::new(SigningError(String::from_utf8_lossy(&output.stderr).trim().to_string())));
     }
-    Ok(())
+    Ok(String::from_utf8(output.stdout)?)
 }
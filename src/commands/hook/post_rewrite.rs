@@ -0,0 +1,27 @@
+use std::error::Error;
+use std::io::{stdin, BufRead};
+use git2::{Oid, Repository};
+use structopt::StructOpt;
+use super::post_commit::copy_transitions;
+
+#[derive(StructOpt, Debug)]
+pub struct PostRewrite {
+    /// The command that rewrote the commits: amend or rebase.
+    command: String,
+}
+
+/// Records the transitions of each rebased commit against its new copy, reading the old and new
+/// IDs of the commits from standard input, one pair per line. Amends are left to post-commit, which
+/// amends the commit again after Git has passed its ID on here.
+pub fn post_rewrite(args: PostRewrite) -> Result<(), Box<dyn Error>> {
+    if args.command != "rebase" { return Ok(()) }
+    let repository = Repository::open_from_env()?;
+    for line in stdin().lock().lines() {
+        let line = line?;
+        let mut ids = line.split_whitespace();
+        if let (Some(original), Some(copy)) = (ids.next(), ids.next()) {
+            copy_transitions(&repository, Oid::from_str(original)?, Oid::from_str(copy)?)?;
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,8 @@
 use std::error::Error;
-use std::fs::File;
-use crate::PROJECT_TEMP_FILE;
+use git2::Repository;
+use super::state::HookState;
 
 pub fn pre_commit() -> Result<(), Box<dyn Error>> {
-    File::create(PROJECT_TEMP_FILE)?;
-    Ok(())
+    let repository = Repository::open_from_env()?;
+    HookState::begin(&repository)
 }
@@ -0,0 +1,195 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{stdin, BufRead};
+use git2::{Commit, Config, Oid, Repository};
+use structopt::StructOpt;
+use crate::model::*;
+
+/// The problems a push would cause, which reject it.
+#[derive(Debug)]
+struct RejectError(Vec<String>);
+impl Display for RejectError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for problem in &self.0 {
+            writeln!(f, "git-project: {}", problem)?;
+        }
+        write!(f, "git-project: The push was rejected, as it would break the project board")
+    }
+}
+impl Error for RejectError {}
+
+#[derive(StructOpt, Debug)]
+pub struct Update {
+    reference: String,
+    old: String,
+    new: String,
+}
+
+/// A column whose tasks may not be moved out of it, from the `project.lockedColumn` config. This
+/// is either `column`, which applies to every project, or `column@project`.
+struct LockedColumn {
+    column: Id,
+    project: Option<Id>,
+}
+
+impl LockedColumn {
+    fn configured(config: &Config) -> Result<Vec<LockedColumn>, Box<dyn Error>> {
+        let mut locked = vec![];
+        if let Ok(mut entries) = config.multivar("project.lockedColumn", None) {
+            while let Some(entry) = entries.next() {
+                let value = match entry?.value() {
+                    Some(value) => value.to_string(),
+                    None => continue,
+                };
+                let mut parts = value.splitn(2, '@');
+                locked.push(LockedColumn {
+                    column: parts.next().unwrap_or_default().into(),
+                    project: parts.next().map(Id::from),
+                });
+            }
+        }
+        Ok(locked)
+    }
+
+    fn applies_to(&self, project: &Project, column: &Column) -> bool {
+        column.id() == &self.column && self.project.as_ref().map(|id| id == project.id()).unwrap_or(true)
+    }
+}
+
+/// Checks that a board is internally consistent.
+fn integrity_problems(git_project: &GitProject) -> Vec<String> {
+    let mut problems = vec![];
+    let mut project_ids = BTreeSet::new();
+    for project in git_project.projects() {
+        if !project.id().is_valid() {
+            problems.push(format!("project ID {:?} is not valid", project.id().as_ref()));
+        }
+        if !project_ids.insert(project.id()) {
+            problems.push(format!("there is more than one project with ID {}", project.id()));
+        }
+
+        let mut column_ids = BTreeSet::new();
+        let mut placed = BTreeSet::new();
+        for column in project.columns() {
+            if !column.id().is_valid() {
+                problems.push(format!("column ID {:?} in project {} is not valid", column.id().as_ref(), project.id()));
+            }
+            if !column_ids.insert(column.id()) {
+                problems.push(format!("there is more than one column with ID {} in project {}", column.id(), project.id()));
+            }
+            for task_id in column.tasks() {
+                if project.task_with_id(task_id).is_none() {
+                    problems.push(format!("column {} refers to unknown task {}@{}", column.id(), task_id, project.id()));
+                }
+                if !placed.insert(task_id) {
+                    problems.push(format!("task {}@{} is in more than one column", task_id, project.id()));
+                }
+            }
+        }
+
+        let mut task_ids = BTreeSet::new();
+        for task in project.tasks() {
+            if !task.id().is_valid() {
+                problems.push(format!("task ID {:?} in project {} is not valid", task.id().as_ref(), project.id()));
+            }
+            if !task_ids.insert(task.id()) {
+                problems.push(format!("there is more than one task with ID {} in project {}", task.id(), project.id()));
+            }
+        }
+    }
+    problems
+}
+
+/// Checks that the changes between two versions of a board follow the configured rules.
+fn rule_problems(old: &GitProject, new: &GitProject, locked_columns: &[LockedColumn]) -> Vec<String> {
+    let mut problems = vec![];
+    for old_project in old.projects() {
+        let new_project = new.projects().iter().find(|project| project.id() == old_project.id());
+        for column in old_project.columns() {
+            if !locked_columns.iter().any(|locked| locked.applies_to(old_project, column)) { continue }
+            for task_id in column.tasks() {
+                let still_locked = new_project
+                    .and_then(|project| project.columns().iter().find(|c| c.id() == column.id()))
+                    .map(|c| c.tasks().contains(task_id))
+                    .unwrap_or(false);
+                if !still_locked {
+                    problems.push(format!("task {}@{} was moved out of the locked column {}", task_id, old_project.id(), column.id()));
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn check_commit(repository: &Repository, commit: &Commit, locked_columns: &[LockedColumn]) -> Result<Vec<String>, Box<dyn Error>> {
+    let new = match GitProject::open_from_tree(repository, &commit.tree()?) {
+        Ok(Some(new)) => new,
+        Ok(None) => return Ok(vec![]),
+        Err(error) => return Ok(vec![format!("the project file could not be read: {}", error)]),
+    };
+    let mut problems = integrity_problems(&new);
+
+    // only the first parent is compared against, as changes merged in from other branches were
+    // already checked in their own commits
+    if let Ok(parent) = commit.parent(0) {
+        if let Ok(Some(old)) = GitProject::open_from_tree(repository, &parent.tree()?) {
+            problems.extend(rule_problems(&old, &new, locked_columns));
+        }
+    }
+    Ok(problems)
+}
+
+/// Checks each commit a ref update introduces, returning the problems found.
+fn check_ref(repository: &Repository, reference: &str, old: &str, new: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let new = Oid::from_str(new)?;
+    if new.is_zero() { return Ok(vec![]) } // deleting a ref cannot break the board
+    let new = match repository.find_object(new, None)?.peel_to_commit() {
+        Ok(commit) => commit,
+        Err(_) => return Ok(vec![]),
+    };
+
+    let mut walk = repository.revwalk()?;
+    walk.push(new.id())?;
+    // commits already reachable from existing refs were checked when they were pushed
+    walk.hide_glob("refs/*")?;
+    let old = Oid::from_str(old)?;
+    if !old.is_zero() {
+        walk.hide(old)?;
+    }
+
+    let locked_columns = LockedColumn::configured(&repository.config()?.snapshot()?)?;
+    let mut problems = vec![];
+    for oid in walk {
+        let commit = repository.find_commit(oid?)?;
+        let short_id = commit.as_object().short_id()?;
+        for problem in check_commit(repository, &commit, &locked_columns)? {
+            problems.push(format!("{} {}: {}", reference, short_id.as_str().unwrap_or_default(), problem));
+        }
+    }
+    Ok(problems)
+}
+
+fn reject(problems: Vec<String>) -> Result<(), Box<dyn Error>> {
+    if problems.is_empty() { return Ok(()) }
+    Err(Box::new(RejectError(problems)))
+}
+
+/// Checks every ref update in the push, as listed on standard input.
+pub fn pre_receive() -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let mut problems = vec![];
+    for line in stdin().lock().lines() {
+        let line = line?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if let [old, new, reference] = parts[..] {
+            problems.extend(check_ref(&repository, reference, old, new)?);
+        }
+    }
+    reject(problems)
+}
+
+pub fn update(args: Update) -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    reject(check_ref(&repository, &args.reference, &args.old, &args.new)?)
+}
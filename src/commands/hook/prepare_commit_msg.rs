@@ -1,13 +1,86 @@
-use structopt::StructOpt;
 use std::error::Error;
+use std::fmt::Write as _;
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use git2::Repository;
+use structopt::StructOpt;
+use crate::model::*;
+use super::{branch, message};
 
 #[derive(StructOpt, Debug)]
 pub struct PrepareCommitMsg {
-    file: String,
-    source: String,
+    file: PathBuf,
+    /// One of message, template, merge, squash, or commit, or nothing for a regular commit.
+    source: Option<String>,
+    /// The commit being amended or reused, when the source is commit.
     amending: Option<String>,
 }
 
 pub fn prepare_commit_msg(args: PrepareCommitMsg) -> Result<(), Box<dyn Error>> {
+    // messages given with -m or -F, merges, squashes, and amends or messages reused with -c are
+    // left as they are
+    match (args.source.as_deref(), args.amending.as_deref()) {
+        (None, None) | (Some("template"), None) => (),
+        _ => return Ok(()),
+    }
+
+    let repository = Repository::open_from_env()?;
+    let config = repository.config()?.snapshot()?;
+    let current_user = match config.get_str("user.email") {
+        Ok(current_user) => current_user,
+        Err(..) => return Ok(()),
+    };
+    let mut contents = read_to_string(&args.file)?;
+    let comment = message::comment_char(&config, &contents);
+    let git_project = GitProject::open()?;
+
+    let mut stubs = String::new();
+    if let Some(task_id) = branch::branch_task(&repository, &git_project)? {
+        let project = git_project.projects().iter().find(|project| project.task_with_id(&task_id).is_some()).unwrap();
+        let task = project.task_with_id(&task_id).unwrap();
+        writeln!(stubs, "{}", comment)?;
+        writeln!(stubs, "{} This branch is for task {}, so it may be left out of these commands:", comment, task_id)?;
+        if let Some(last_column) = project.columns().last() {
+            writeln!(stubs, "{} [is {}]    {}", comment, last_column.id(), task.name())?;
+        }
+        writeln!(stubs, "{} [+tag]", comment)?;
+    }
+    for project in git_project.projects() {
+        let last_column = match project.columns().last() {
+            Some(column) => column,
+            None => continue,
+        };
+        let my_tasks: Vec<&Task> = project
+            .tasks()
+            .iter()
+            .filter(|task| task.assignee() == Some(current_user))
+            .filter(|task| !last_column.tasks().contains(task.id()))
+            .collect();
+        if my_tasks.is_empty() { continue; }
+
+        writeln!(stubs, "{}", comment)?;
+        writeln!(stubs, "{} {} ({})", comment, project.name(), project.id())?;
+        for task in my_tasks {
+            let ambiguous = git_project.projects().iter()
+                .filter(|other| other.task_with_id(task.id()).is_some())
+                .count() > 1;
+            let reference = if ambiguous { format!("{}@{}", task.id(), project.id()) } else { task.id().to_string() };
+            writeln!(stubs, "{} [{} is {}]    {}", comment, reference, last_column.id(), task.name())?;
+            writeln!(stubs, "{} [{} +tag]", comment, reference)?;
+        }
+    }
+    if stubs.is_empty() { return Ok(()) }
+
+    let mut block = String::new();
+    writeln!(block)?;
+    writeln!(block, "{} git-project: uncomment any of these commands to update your tasks with this commit.", comment)?;
+    block.push_str(&stubs);
+    // with --verbose, the diff is already below the scissors line, where the stubs would be thrown
+    // away, so they go above it
+    match message::scissors_position(&contents, comment) {
+        Some(position) => contents.insert_str(position, &block),
+        None => contents.push_str(&block),
+    }
+    write(&args.file, contents)?;
     Ok(())
 }
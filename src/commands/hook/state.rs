@@ -0,0 +1,122 @@
+use std::collections::hash_map::DefaultHasher;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{read_to_string, remove_file, write};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use git2::{Commit, Oid, Repository};
+use serde::{Serialize, Deserialize};
+use crate::model::{GitProject, Transition};
+use crate::PROJECT_STATE_FILE_NAME;
+
+#[derive(Debug)]
+struct StateError(PathBuf, serde_json::Error);
+impl Display for StateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "git-project: Could not read the state of the commit in progress from {}: {}", self.0.display(), self.1)
+    }
+}
+impl Error for StateError {}
+
+/// How long the state of a commit is kept between hooks. Anything older was left behind by a
+/// commit that never finished.
+const STATE_EXPIRY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs()).unwrap_or_default()
+}
+
+fn message_hash(message: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    message.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The state of a commit in progress, passed between the hooks it runs. This is started by the
+/// pre-commit hook, and completed by commit-msg with the transitions that post-commit should apply.
+///
+/// Not every commit runs every hook (e.g. `--no-verify` skips pre-commit and commit-msg, and merges
+/// skip pre-commit and post-commit), and commits may be aborted part way, so each hook checks that
+/// the state was left by the same commit before using it.
+#[derive(Serialize, Deserialize)]
+pub struct HookState {
+    /// HEAD at the time the commit was started, if there was one.
+    head: Option<String>,
+    /// When the commit was started, in seconds since the Unix epoch.
+    time: u64,
+    /// The path of the commit message file given to the commit-msg hook, and the hash of its
+    /// contents, once that hook has run.
+    message: Option<(u64, PathBuf)>,
+    /// The board with the commands in the commit message applied to it, once commit-msg has run.
+    board: Option<GitProject>,
+    transitions: Vec<Transition>,
+}
+
+impl HookState {
+    fn path(repository: &Repository) -> PathBuf {
+        repository.path().join(PROJECT_STATE_FILE_NAME)
+    }
+
+    fn current_head(repository: &Repository) -> Option<String> {
+        repository.head().ok().and_then(|head| head.target()).map(|head| head.to_string())
+    }
+
+    /// Starts the state of a new commit, replacing any left over from a previous one.
+    pub fn begin(repository: &Repository) -> Result<(), Box<dyn Error>> {
+        HookState { head: Self::current_head(repository), time: now(), message: None, board: None, transitions: vec![] }.save(repository)
+    }
+
+    /// Reads and removes the state of the commit in progress. The state is not returned if it has
+    /// expired.
+    pub fn take(repository: &Repository) -> Result<Option<HookState>, Box<dyn Error>> {
+        let path = Self::path(repository);
+        if !path.exists() { return Ok(None) }
+        let contents = read_to_string(&path)?;
+        remove_file(&path)?;
+
+        let state: HookState = serde_json::from_str(&contents).map_err(|error| StateError(path, error))?;
+        if now().saturating_sub(state.time) > STATE_EXPIRY.as_secs() { return Ok(None) }
+        Ok(Some(state))
+    }
+
+    pub fn save(&self, repository: &Repository) -> Result<(), Box<dyn Error>> {
+        write(Self::path(repository), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Whether this state was started for the commit now reaching the commit-msg hook, which has not
+    /// been made yet, so HEAD should not have moved.
+    pub fn started_at_head(&self, repository: &Repository) -> bool {
+        self.message.is_none() && self.head == Self::current_head(repository)
+    }
+
+    /// Completes the state with the commit message, and the board and transitions that result from
+    /// the commands in it.
+    pub fn complete(&mut self, message_file: &Path, message: &str, board: GitProject, transitions: Vec<Transition>) {
+        self.message = Some((message_hash(message), message_file.to_path_buf()));
+        self.board = Some(board);
+        self.transitions = transitions;
+    }
+
+    /// Whether this state was completed for the commit that has just been made: its message file is
+    /// unchanged, and the commit was made on top of (or amended) the HEAD the state was started at.
+    pub fn completed_for(&self, repository: &Repository, commit: &Commit) -> bool {
+        let (hash, path) = match &self.message {
+            Some(message) => message,
+            None => return false,
+        };
+        if read_to_string(path).map(|message| message_hash(&message)).ok() != Some(*hash) { return false }
+
+        let parent = commit.parent_id(0).ok();
+        let head = self.head.as_ref().and_then(|head| Oid::from_str(head).ok());
+        if parent == head { return true }
+        let amended = head.and_then(|head| repository.find_commit(head).ok());
+        amended.map(|amended| amended.parent_id(0).ok() == parent).unwrap_or(false)
+    }
+
+    /// The board and transitions resulting from the commit message, if commit-msg has run.
+    pub fn into_changes(self) -> Option<(GitProject, Vec<Transition>)> {
+        Some((self.board?, self.transitions))
+    }
+}
@@ -1,43 +1,153 @@
-use std::env::current_dir;
 use std::error::Error;
-use std::fs::{OpenOptions, read_to_string};
-use std::path::PathBuf;
+use std::fs::{OpenOptions, read_to_string, write, remove_file, create_dir_all};
+use std::path::{Path, PathBuf};
 use std::io::Write;
 #[cfg(unix)] use std::fs::{set_permissions, metadata};
 #[cfg(unix)] use std::os::unix::fs::PermissionsExt;
 use regex::Regex;
 use git2::Repository;
+use structopt::StructOpt;
 
-fn hook(path: PathBuf, name: &str) -> Result<(), Box<dyn Error>> {
-    if path.exists() {
-        let pattern = Regex::new(&format!("^[^#]*git project hook {}", name)).unwrap();
-        let contents = read_to_string(&path)?;
-        for line in contents.lines() {
-            if pattern.is_match(line) { return Ok(()); }
-        }
+const HOOKS: [&str; 5] = ["pre-commit", "prepare-commit-msg", "commit-msg", "post-commit", "post-rewrite"];
+const SHEBANG: &str = "#!/bin/sh";
+
+#[derive(StructOpt, Debug)]
+pub struct Hooks {
+    /// Shows which of the git-project hooks are installed, and where.
+    #[structopt(long, conflicts_with = "uninstall")]
+    status: bool,
+    /// Removes the git-project hooks, leaving the rest of your hooks as they were.
+    #[structopt(long)]
+    uninstall: bool,
+}
+
+fn marker(name: &str) -> String {
+    format!("# git-project {} hook", name)
+}
+
+fn is_installed(path: &Path, name: &str) -> Result<bool, Box<dyn Error>> {
+    if !path.exists() { return Ok(false) }
+    let pattern = Regex::new(&format!("^[^#]*git project hook {}", name)).unwrap();
+    Ok(read_to_string(path)?.lines().any(|line| pattern.is_match(line)))
+}
+
+fn install(path: &Path, name: &str) -> Result<(), Box<dyn Error>> {
+    if is_installed(path, name)? { return Ok(()) }
+    let created = !path.exists();
+    let mut file = OpenOptions::new().append(true).create(true).open(path)?;
+    if created {
+        writeln!(file, "{}", SHEBANG)?;
     }
-    let mut file = OpenOptions::new().append(true).create(true).open(&path)?;
     writeln!(file)?;
-    writeln!(file, "# git-project {} hook", name)?;
-    writeln!(file, "git project hook {} $@", name)?;
+    writeln!(file, "{}", marker(name))?;
+    writeln!(file, "git project hook {} \"$@\"", name)?;
     std::mem::drop(file);
     #[cfg(unix)] {
-        let mut permissions = metadata(&path)?.permissions();
-        permissions.set_mode(0o777);
-        set_permissions(&path, permissions)?;
+        let mut permissions = metadata(path)?.permissions();
+        permissions.set_mode(0o755);
+        set_permissions(path, permissions)?;
     }
 
     Ok(())
 }
 
-pub fn hooks() -> Result<(), Box<dyn Error>> {
-    let repository = Repository::discover(current_dir()?)?;
-    let hooks = repository.path().join("hooks");
+/// Removes the lines added by `install`: the marker comment, the command following it, and the
+/// blank line before it. If nothing but the shebang is left, the hook file is removed entirely.
+fn uninstall(path: &Path, name: &str) -> Result<bool, Box<dyn Error>> {
+    if !path.exists() { return Ok(false) }
+    let contents = read_to_string(path)?;
+    let marker = marker(name);
+    let command = format!("git project hook {}", name);
 
-    hook(hooks.join("pre-commit"), "pre-commit")?;
-    hook(hooks.join("post-commit"), "post-commit")?;
-    hook(hooks.join("commit-msg"), "commit-msg")?;
-    hook(hooks.join("prepare-commit-msg"), "prepare-commit-msg")?;
+    let mut lines: Vec<&str> = contents.lines().collect();
+    let mut removed = false;
+    while let Some(index) = lines.iter().position(|line| line.trim() == marker) {
+        let mut end = index + 1;
+        if lines.get(end).map(|line| line.trim_start().starts_with(&command)).unwrap_or(false) {
+            end += 1;
+        }
+        let start = if index > 0 && lines[index - 1].trim().is_empty() { index - 1 } else { index };
+        lines.drain(start..end);
+        removed = true;
+    }
+    if !removed { return Ok(false) }
+
+    if lines.iter().all(|line| line.trim().is_empty() || line.trim() == SHEBANG) {
+        remove_file(path)?;
+    } else {
+        write(path, lines.iter().map(|line| format!("{}\n", line)).collect::<String>())?;
+    }
+    Ok(true)
+}
 
+/// The directory Git runs hooks from. This is `core.hooksPath` if set, or otherwise the hooks
+/// directory shared by all worktrees of a repository.
+pub fn hooks_dir(repository: &Repository) -> Result<PathBuf, Box<dyn Error>> {
+    let config = repository.config()?.snapshot()?;
+    if let Ok(hooks_path) = config.get_path("core.hooksPath") {
+        if hooks_path.is_absolute() { return Ok(hooks_path) }
+        // relative paths are relative to where hooks are run from, which is the root of the
+        // working tree, or the Git directory for bare repositories
+        let base = repository.workdir().unwrap_or_else(|| repository.path());
+        return Ok(base.join(hooks_path));
+    }
+
+    let mut common_dir = repository.path().to_path_buf();
+    if repository.is_worktree() {
+        let relative = read_to_string(repository.path().join("commondir"))?;
+        common_dir = common_dir.join(relative.trim());
+    }
+    Ok(common_dir.join("hooks"))
+}
+
+/// Adds all of the git-project hooks to the repository.
+pub fn install_hooks() -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let hooks = hooks_dir(&repository)?;
+    create_dir_all(&hooks)?;
+    for name in HOOKS.iter() {
+        install(&hooks.join(name), name)?;
+    }
+    // the commits shown for a task are found through the board notes, which Git does not fetch
+    // unless told to
+    if repository.find_remote("origin").is_ok() {
+        super::notes::setup("origin")?;
+    }
+    Ok(())
+}
+
+fn uninstall_hooks() -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let hooks = hooks_dir(&repository)?;
+    for name in HOOKS.iter() {
+        if uninstall(&hooks.join(name), name)? {
+            println!("Removed the {} hook", name);
+        }
+    }
     Ok(())
 }
+
+fn status() -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let hooks = hooks_dir(&repository)?;
+    println!("Hooks are run from {}", hooks.display());
+    for name in HOOKS.iter() {
+        let path = hooks.join(name);
+        if is_installed(&path, name)? {
+            println!("\t\x1b[32minstalled\x1b[0m     {}", name);
+        } else {
+            println!("\t\x1b[31mnot installed\x1b[0m {}", name);
+        }
+    }
+    Ok(())
+}
+
+pub fn hooks(args: Hooks) -> Result<(), Box<dyn Error>> {
+    if args.status {
+        status()
+    } else if args.uninstall {
+        uninstall_hooks()
+    } else {
+        install_hooks()
+    }
+}
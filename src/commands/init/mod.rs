@@ -1,7 +1,4 @@
-use std::env::current_dir;
 use std::error::Error;
-use std::fs::File;
-use std::io::Write;
 use std::fmt::{self, Display, Formatter};
 use git2::Repository;
 use structopt::StructOpt;
@@ -20,6 +17,9 @@ pub struct Init {
     /// Including this flag is the same as running `git project hooks` separately
     #[structopt(long)]
     force_hooks: bool,
+    /// The format to store the project file in: toml, json, or yaml.
+    #[structopt(long, default_value = "toml")]
+    format: Format,
 }
 
 #[derive(Debug)]
@@ -31,13 +31,24 @@ impl Display for InitError {
 }
 impl Error for InitError {}
 
+#[derive(Debug)]
+struct BareInitError;
+impl Display for BareInitError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Cannot initialize a project in a bare repository. Run this command in a repository with a working tree instead.")
+    }
+}
+impl Error for BareInitError {}
+
 pub fn init(args: Init) -> Result<(), Box<dyn Error>> {
-    let repository = Repository::discover(current_dir()?)?;
-    let workdir = repository.workdir().unwrap();
-    let root = workdir.join(PROJECT_FILE_NAME);
-    if root.exists() { return Err(Box::new(InitError)) }
+    let repository = Repository::open_from_env()?;
+    let workdir = match repository.workdir() {
+        Some(workdir) => workdir,
+        None => return Err(Box::new(BareInitError)),
+    };
+    if Format::ALL.iter().any(|format| workdir.join(format.file_name()).exists()) { return Err(Box::new(InitError)) }
 
-    let hooks_path = repository.path().join("hooks");
+    let hooks_path = super::hooks_dir(&repository)?;
     let hooks_exist = hooks_path.exists() && (hooks_path.join("commit-msg").exists() || hooks_path.join("prepare-commit-msg").exists());
     let will_add_hooks = !args.no_hooks && (!hooks_exist || args.force_hooks);
 
@@ -67,10 +78,11 @@ Here's how:
 "#, hooks_message))
         .build().unwrap();
 
-    let git_project = GitProject::new()
+    let mut git_project = GitProject::new()
         .project(Project::new(name.to_ascii_lowercase().split(|ch: char| ch.is_whitespace() || ch == '-' || ch == '_').filter_map(|word| word.chars().next()).collect::<String>())
             .name(name)
             .description("Write a description of your project here.")
+            .task_id_pattern("{project}-{n}")
             .column(Column::new("new")
                 .name("New")
                 .description("Tasks that have not yet been started")
@@ -92,12 +104,11 @@ Here's how:
         )
         .build().unwrap();
 
-    let project_string = toml::to_string_pretty(&git_project)?;
-    let mut file = File::create(root)?;
-    write!(file, "{}", project_string)?;
+    git_project.set_format(args.format);
+    git_project.save()?;
 
     if will_add_hooks {
-        super::hooks()?;
+        super::install_hooks()?;
     }
 
     super::current()
@@ -1,11 +1,17 @@
+mod convert;
 mod current;
 mod hook;
 mod hooks;
 mod init;
+mod notes;
 mod open;
+mod parse_message;
 
+pub use convert::*;
 pub use current::*;
 pub use hook::*;
 pub use hooks::*;
 pub use open::*;
 pub use init::*;
+pub use notes::*;
+pub use parse_message::*;
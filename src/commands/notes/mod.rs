@@ -0,0 +1,169 @@
+use std::error::Error;
+use std::collections::{BTreeMap, HashMap};
+use git2::{Note, Oid, Repository, Sort};
+use structopt::StructOpt;
+use crate::model::*;
+use crate::NOTES_REF;
+
+#[derive(StructOpt, Debug)]
+pub enum Notes {
+    /// Shows the board changes made by a commit.
+    Show {
+        /// The commit to show the board changes of.
+        #[structopt(default_value = "HEAD")]
+        commit: String,
+    },
+    /// Configures a remote to fetch the board notes, and `git log` to display them.
+    ///
+    /// Notes fetched from the remote are stored separately from your own, under
+    /// refs/notes/remotes/<remote>/git-project, and are still shown by `git project notes show`.
+    /// Use `git notes --ref git-project merge` to combine them with your own. Your own notes
+    /// can be shared using `git push <remote> refs/notes/git-project`.
+    Setup {
+        /// The remote to share notes with.
+        #[structopt(default_value = "origin")]
+        remote: String,
+    },
+}
+
+/// Records the transitions applied by a commit in its notes.
+pub fn record_transitions(repository: &Repository, commit: Oid, transitions: &[Transition]) -> Result<(), Box<dyn Error>> {
+    if transitions.is_empty() { return Ok(()) }
+    let note: String = transitions.iter().map(|transition| format!("{}\n", transition)).collect();
+    let signature = repository.signature()?;
+    repository.note(&signature, &signature, Some(NOTES_REF), commit, &note, true)?;
+    Ok(())
+}
+
+/// The local notes, followed by any fetched from remotes, of those that exist.
+fn notes_refs(repository: &Repository) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut refs = vec![];
+    if repository.find_reference(NOTES_REF).is_ok() {
+        refs.push(NOTES_REF.to_string());
+    }
+    for reference in repository.references_glob("refs/notes/remotes/*/git-project")? {
+        if let Some(name) = reference?.name() {
+            refs.push(name.to_string());
+        }
+    }
+    Ok(refs)
+}
+
+fn parse_note(note: &Note) -> Vec<Transition> {
+    note.message()
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.parse().ok())
+        .collect()
+}
+
+/// Reads the transitions recorded for a commit, from either the local notes or those fetched from
+/// a remote.
+pub fn recorded_transitions(repository: &Repository, commit: Oid) -> Result<Vec<Transition>, Box<dyn Error>> {
+    for notes_ref in notes_refs(repository)? {
+        if let Ok(note) = repository.find_note(Some(&notes_ref), commit) {
+            return Ok(parse_note(&note));
+        }
+    }
+    Ok(vec![])
+}
+
+/// The commits in the history of HEAD that changed each task, by their ID and summary. A commit
+/// cannot contain its own ID, so these links are found through the transitions recorded in the
+/// commits' notes rather than kept in the project file.
+#[derive(Default, Debug)]
+pub struct TaskCommits {
+    commits: BTreeMap<(Id, Id), Vec<(Oid, String)>>,
+}
+
+impl TaskCommits {
+    /// Reads every note once, and then walks the history of HEAD once to keep only the commits in
+    /// it, oldest first.
+    pub fn load(repository: &Repository) -> Result<TaskCommits, Box<dyn Error>> {
+        let mut noted: HashMap<Oid, Vec<Transition>> = HashMap::new();
+        for notes_ref in notes_refs(repository)? {
+            for ids in repository.notes(Some(&notes_ref))? {
+                let (_, commit_id) = ids?;
+                if noted.contains_key(&commit_id) { continue }
+                noted.insert(commit_id, parse_note(&repository.find_note(Some(&notes_ref), commit_id)?));
+            }
+        }
+
+        let mut task_commits = TaskCommits::default();
+        if noted.is_empty() || repository.head().is_err() { return Ok(task_commits) }
+        let mut walk = repository.revwalk()?;
+        walk.push_head()?;
+        walk.set_sorting(Sort::TIME)?;
+        // the walk goes back from HEAD, so it can stop once it has passed every noted commit
+        let mut remaining = noted.len();
+        for commit_id in walk {
+            if remaining == 0 { break }
+            let commit_id = commit_id?;
+            let transitions = match noted.get(&commit_id) {
+                Some(transitions) => transitions,
+                None => continue,
+            };
+            remaining -= 1;
+            let summary = repository.find_commit(commit_id)?.summary().unwrap_or_default().to_string();
+            for transition in transitions {
+                let commits = task_commits.commits.entry((transition.project().clone(), transition.task().clone())).or_default();
+                if !commits.iter().any(|(id, _)| *id == commit_id) {
+                    commits.push((commit_id, summary.clone()));
+                }
+            }
+        }
+        for commits in task_commits.commits.values_mut() {
+            commits.reverse();
+        }
+        Ok(task_commits)
+    }
+
+    pub fn of(&self, project: &Id, task: &Id) -> &[(Oid, String)] {
+        self.commits.get(&(project.clone(), task.clone())).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+fn show(commit: &str) -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let commit = repository.revparse_single(commit)?.peel_to_commit()?;
+    let transitions = recorded_transitions(&repository, commit.id())?;
+    if transitions.is_empty() {
+        println!("Commit \x1b[33m{}\x1b[0m did not change the board", commit.id());
+        return Ok(());
+    }
+    println!("Commit \x1b[33m{}\x1b[0m changed the board:", commit.id());
+    for transition in transitions {
+        println!("\t{}", transition);
+    }
+    Ok(())
+}
+
+pub fn setup(remote: &str) -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    repository.find_remote(remote)?;
+
+    // a push refspec is not configured, as that would stop `git push` from pushing the current branch
+    let fetch_refspec = format!("+{}:refs/notes/remotes/{}/git-project", NOTES_REF, remote);
+    let config = repository.config()?.snapshot()?;
+    let mut configured = false;
+    config.entries(Some(&format!("remote.{}.fetch", remote)))?.for_each(|entry| {
+        if entry.value() == Some(fetch_refspec.as_str()) { configured = true; }
+    })?;
+    if !configured {
+        repository.remote_add_fetch(remote, &fetch_refspec)?;
+    }
+
+    let mut config = repository.config()?.open_level(git2::ConfigLevel::Local)?;
+    config.set_multivar("notes.displayRef", &format!("^{}$", regex::escape(NOTES_REF)), NOTES_REF)?;
+    config.set_multivar("notes.rewriteRef", &format!("^{}$", regex::escape(NOTES_REF)), NOTES_REF)?;
+
+    println!("Board notes will now be fetched from {}. Share yours with `git push {} {}`", remote, remote, NOTES_REF);
+    Ok(())
+}
+
+pub fn notes(args: Notes) -> Result<(), Box<dyn Error>> {
+    match args {
+        Notes::Show { commit } => show(&commit),
+        Notes::Setup { remote } => setup(&remote),
+    }
+}
@@ -1,18 +1,20 @@
-use std::env::current_dir;
 use std::error::Error;
 use git2::Repository;
 use crate::model::*;
 
 mod state;
-use state::State;
+use state::{Colors, Keymap, State};
 
 pub fn open() -> Result<(), Box<dyn Error>> {
-    let repository = Repository::discover(current_dir()?)?;
+    let repository = Repository::open_from_env()?;
     let config = repository.config()?.snapshot()?;
     let current_user = config.get_string("user.email")?;
 
     let git_project = GitProject::open()?;
 
-    let state = State::new(git_project, current_user);
+    let keymap = Keymap::from_config(&config)?;
+    let colors = Colors::from_config(&git_project, &config)?;
+
+    let state = State::new(git_project, current_user, keymap, colors);
     state.run()
 }
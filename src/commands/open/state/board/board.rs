@@ -7,14 +7,21 @@ pub fn board(state: State) -> impl View {
     let project = &git_project.projects()[state.selected_project.get()];
 
     let header = LinearLayout::vertical()
-        .child(TextView::new(project.name()).effect(Effect::Bold))
-        .child(TextView::new(project.description()));
-
-    let project_board = project.columns().iter()
-        .map(|col| column::column(state.clone(), project, col))
-        .fold(LinearLayout::horizontal(), LinearLayout::child);
+        .child(TextView::new(project.name()).style(Effect::Bold))
+        .child(TextView::new(project.description()))
+        .child(TextView::new(state.filter.borrow().describe()).with_name("board-filter"));
 
     LinearLayout::vertical()
-        .child(Panel::new(PaddedView::new((1, 1, 0, 0), header)).full_width())
-        .child(ScrollView::new(project_board).full_screen())
+        .child(Panel::new(PaddedView::lrtb(1, 1, 0, 0, header)).full_width())
+        .child(ScrollView::new(columns(state.clone()).with_name("board-columns")).full_screen())
+}
+
+/// The columns of the selected project, showing the cards that match the current filter.
+pub fn columns(state: State) -> LinearLayout {
+    let git_project = state.git_project.borrow();
+    let project = &git_project.projects()[state.selected_project.get()];
+
+    project.columns().iter()
+        .map(|col| column::column(state.clone(), project, col))
+        .fold(LinearLayout::horizontal(), LinearLayout::child)
 }
@@ -1,18 +1,23 @@
-use cursive::{views::*, view::*};
+use cursive::{views::*, view::*, theme::*, utils::markup::StyledString};
 use super::State;
 use super::task;
 use crate::model::*;
 
 pub fn column(state: State, project: &Project, column: &Column) -> impl View {
+    let filter = state.filter.borrow();
     let column_view = column.tasks().iter()
         .filter_map(|task_id| project.task_with_id(task_id))
+        .filter(|task| filter.matches(task, &state.current_user))
         .map(|t| task::card(state.clone(), t))
         .fold(LinearLayout::vertical(), LinearLayout::child);
 
-    let scroll_view = ScrollView::new(column_view)
-        .full_height()
-        .fixed_width(80);
+    // a column with a colour gets a strip of it across the top
+    let mut contents = LinearLayout::vertical();
+    if let Some(color) = state.colors.column(column) {
+        contents.add_child(TextView::new(StyledString::styled(" ".repeat(80), ColorStyle::new(color, color))).no_wrap());
+    }
+    contents.add_child(ScrollView::new(column_view).full_height());
 
-    Panel::new(scroll_view)
+    Panel::new(contents.fixed_width(80))
         .title(column.name())
 }
@@ -1,5 +1,5 @@
 use cursive::{views::*, view::*};
-use super::State;
+use super::{Action, State};
 
 mod board;
 mod column;
@@ -13,3 +13,5 @@ pub fn view(state: State) -> impl View {
         .child(board::board(state))
         .full_screen()
 }
+
+pub use board::columns;
@@ -1,5 +1,5 @@
-use cursive::{Cursive, views::*, view::*, event};
-use super::State;
+use cursive::{Cursive, views::*, view::*};
+use super::{Action, State};
 
 pub fn new(state: State) -> impl View {
     let git_project = state.git_project.borrow();
@@ -12,10 +12,10 @@ pub fn new(state: State) -> impl View {
             state.reload(s);
         }})
         .selected(state.selected_project.get())
-        .with_id("nav-projects");
+        .with_name("nav-projects");
 
     let delete_project = { let state = state.clone(); move |s: &mut Cursive| {
-        let nav_projects = s.find_id::<SelectView<usize>>("nav-projects").unwrap();
+        let nav_projects = s.find_name::<SelectView<usize>>("nav-projects").unwrap();
         let i = *nav_projects.selection().unwrap();
 
         let git_project = state.git_project.borrow();
@@ -25,17 +25,16 @@ pub fn new(state: State) -> impl View {
         }
         let project = git_project.projects().iter().skip(i).next().unwrap();
 
+        let description = format!("delete project {}", project.name());
         state.confirm(s, format!("Delete {}?", project.name()), { let state = state.clone(); move |s| { 
-            state.git_project.borrow_mut().delete_project(i); 
+            state.change(description.clone(), |git_project| { git_project.delete_project(i); true });
             state.selected_project.set(i.saturating_sub(1));
             state.reload(s);
         }});
     }};
 
-    let evented = OnEventView::new(left_nav)
-        .on_event(event::Key::Del, delete_project.clone())
-        .on_event(event::Key::Backspace, delete_project);
+    let evented = state.keymap.bind(OnEventView::new(left_nav), Action::Delete, delete_project);
 
-    Panel::new(PaddedView::new((1, 1, 0, 0), evented))
+    Panel::new(PaddedView::lrtb(1, 1, 0, 0, evented))
         .fixed_width(20)
 }
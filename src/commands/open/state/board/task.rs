@@ -1,7 +1,61 @@
-use cursive::{Cursive, align::*, theme::*, traits::*, views::*, event, utils::markup::StyledString};
-use super::State;
+use std::rc::Rc;
+use cursive::{Cursive, Vec2, align::*, theme::*, traits::*, views::*, utils::markup::StyledString, wrap_impl};
+use cursive::event::{Event, EventResult, MouseButton, MouseEvent};
+use cursive::view::ViewWrapper;
+use super::{Action, State};
 use crate::model::*;
 
+/// Runs a callback when the view is clicked anywhere that its children don't handle the click
+/// themselves.
+struct Clickable<V> {
+    view: V,
+    size: Vec2,
+    callback: Rc<dyn Fn(&mut Cursive)>,
+}
+
+impl<V: View> ViewWrapper for Clickable<V> {
+    wrap_impl!(self.view: V);
+
+    fn wrap_layout(&mut self, size: Vec2) {
+        self.size = size;
+        self.view.layout(size);
+    }
+
+    fn wrap_on_event(&mut self, event: Event) -> EventResult {
+        match self.view.on_event(event.clone()) {
+            EventResult::Ignored => match event {
+                Event::Mouse { event: MouseEvent::Release(MouseButton::Left), position, offset }
+                    if position.fits_in_rect(offset, self.size) => {
+                    let callback = self.callback.clone();
+                    EventResult::with_cb(move |s| callback(s))
+                }
+                _ => EventResult::Ignored,
+            },
+            result => result,
+        }
+    }
+}
+
+/// The task's tags as chips in their colours, followed by who it is assigned to.
+fn labels(state: &State, task: &Task) -> StyledString {
+    let mut labels = StyledString::new();
+    for tag in task.tags() {
+        let chip = format!(" {} ", tag);
+        match state.colors.tag(tag) {
+            Some(color) => labels.append_styled(chip, ColorStyle::new(PaletteColor::View, color)),
+            None => labels.append_styled(chip, Effect::Reverse),
+        }
+        labels.append_plain(" ");
+    }
+    if let Some(assignee) = task.assignee() {
+        match state.colors.assignee(assignee) {
+            Some(color) => labels.append_styled(assignee, color),
+            None => labels.append_plain(assignee),
+        }
+    }
+    labels
+}
+
 pub fn card(state: State, task: &Task) -> impl View {
     let mut description_text = StyledString::styled(task.name(), Effect::Bold);
     if !task.name().ends_with(|ch: char| ch.is_ascii_punctuation()) {
@@ -11,34 +65,38 @@ pub fn card(state: State, task: &Task) -> impl View {
     description_text.append_plain(" ");
     description_text.append_plain(task.short_description());
 
-    let button = Button::new("Details", { let state = state.clone(); let task = task.clone(); move |s| { 
+    let show_task = { let state = state.clone(); let task = task.clone(); move |s: &mut Cursive| { 
         state.show_task(task.clone(), s) 
-    }});
+    }};
+    let button = Button::new("Details", show_task.clone());
 
     let delete_task = { let state = state.clone(); let task = task.clone(); move |s: &mut Cursive| {
         state.confirm(s, format!("Delete task {}?", task.id()), { let task = task.clone(); let state = state.clone(); move |s| {
-            let mut git_project = state.git_project.borrow_mut();
-            let current_project = &mut git_project.projects_mut()[state.selected_project.get()];
-            current_project.delete_task(task.id());
-            std::mem::drop(git_project);
+            let selected_project = state.selected_project.get();
+            state.change(format!("delete task {}", task.id()), |git_project| {
+                git_project.projects_mut()[selected_project].delete_task(task.id());
+                true
+            });
             state.reload(s);
         }});
     }};
-    let event_handler = OnEventView::new(button)
-        .on_event(event::Key::Del, delete_task.clone())
-        .on_event(event::Key::Backspace, delete_task.clone())
-        .on_event('l', { let state = state.clone(); let task = task.clone(); move |s| { state.move_task_right(&task, s) }})
-        .on_event('h', { let state = state.clone(); let task = task.clone(); move |s| { state.move_task_left(&task, s) }});
+    let keymap = &state.keymap;
+    let event_handler = keymap.bind(OnEventView::new(button), Action::Delete, delete_task);
+    let event_handler = keymap.bind(event_handler, Action::MoveRight, { let state = state.clone(); let task = task.clone(); move |s| { state.move_task_right(&task, s) }});
+    let event_handler = keymap.bind(event_handler, Action::MoveLeft, { let state = state.clone(); let task = task.clone(); move |s| { state.move_task_left(&task, s) }});
+    let event_handler = keymap.bind(event_handler, Action::MoveUp, { let state = state.clone(); let task = task.clone(); move |s| { state.move_task_up(&task, s) }});
+    let event_handler = keymap.bind(event_handler, Action::MoveDown, { let state = state.clone(); let task = task.clone(); move |s| { state.move_task_down(&task, s) }});
 
     let actions = LinearLayout::horizontal()
-        .child(DummyView.full_width())
+        .child(TextView::new(labels(&state, task)).full_width())
         .child(event_handler);
     let task_contents = LinearLayout::vertical()
-        .child(PaddedView::new((0, 0, 1, 1), TextView::new(description_text)))
+        .child(PaddedView::lrtb(0, 0, 1, 1, TextView::new(description_text)))
         .child(actions);
-    Panel::new(PaddedView::new((1, 1, 0, 0), task_contents))
+    let panel = Panel::new(PaddedView::lrtb(1, 1, 0, 0, task_contents))
         .title(task.id())
-        .title_position(HAlign::Left)
+        .title_position(HAlign::Left);
+    Clickable { view: panel, size: Vec2::zero(), callback: Rc::new(show_task) }
         .full_width()
-        .with_id(task.id())
+        .with_name(task.id())
 }
@@ -0,0 +1,166 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use cursive::theme::{self, BaseColor, BorderStyle, Color, PaletteColor};
+use git2::Config;
+use crate::model::*;
+
+#[derive(Debug)]
+struct ThemeError(String);
+impl Display for ThemeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl Error for ThemeError {}
+
+/// Reads `name=colour` entries from a multivar, such as `project.tagColor`.
+fn configured_colors(config: &Config, key: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut colors = vec![];
+    if let Ok(mut entries) = config.multivar(key, None) {
+        while let Some(entry) = entries.next() {
+            let value = match entry?.value() {
+                Some(value) => value.to_string(),
+                None => continue,
+            };
+            let mut parts = value.rsplitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(color), Some(name)) => colors.push((name.trim().to_string(), color.trim().to_string())),
+                _ => return Err(Box::new(ThemeError(format!("\"{}\" in {} should be written as name=colour.", value, key)))),
+            }
+        }
+    }
+    Ok(colors)
+}
+
+/// Parses a colour the way cursive does, but without panicking on the values cursive cannot cope
+/// with, which are the ones that are not ASCII, and without accepting hex codes with other digits.
+fn parse(color: &str) -> Option<Color> {
+    let valid = match color.strip_prefix('#').or_else(|| color.strip_prefix("0x")) {
+        Some(hex) => (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|ch| ch.is_ascii_hexdigit()),
+        None => color.is_ascii(),
+    };
+    if valid { Color::parse(color) } else { None }
+}
+
+fn parse_color(color: &str) -> Result<Color, Box<dyn Error>> {
+    parse(color).ok_or_else(|| -> Box<dyn Error> {
+        Box::new(ThemeError(format!("Unknown colour \"{}\". Use a name such as red or light blue, or a hex code such as #ff8800.", color)))
+    })
+}
+
+/// One of the built in palettes, or a cursive theme file.
+fn palette(name: &str, base: &Path) -> Result<theme::Theme, Box<dyn Error>> {
+    use BaseColor::*;
+    use Color::*;
+    use PaletteColor::*;
+
+    let mut theme = theme::Theme::default();
+    let colors = match name {
+        "default" => return Ok(theme),
+        "dark" => [Dark(Black), Dark(Black), Light(White), Light(Blue), Light(Cyan), Light(Red), Light(Yellow), Light(Blue), Dark(White)],
+        "light" => [Light(White), Light(White), Dark(Black), Dark(Blue), Dark(Magenta), Dark(Red), Dark(Magenta), Dark(Blue), Light(Black)],
+        "terminal" => [TerminalDefault, TerminalDefault, TerminalDefault, Dark(Blue), Dark(Magenta), Dark(Red), Dark(Magenta), Dark(Blue), Light(Black)],
+        path => {
+            let path: PathBuf = base.join(path);
+            return theme::load_theme_file(&path).map_err(|error| -> Box<dyn Error> {
+                let reason = match error {
+                    theme::Error::Io(error) => error.to_string(),
+                    theme::Error::Parse(error) => error.to_string(),
+                };
+                Box::new(ThemeError(format!("Could not load the theme {}: {}", path.display(), reason)))
+            });
+        }
+    };
+    let [background, view, primary, secondary, tertiary, title_primary, title_secondary, highlight, highlight_inactive] = colors;
+    let palette = &mut theme.palette;
+    palette[Background] = background;
+    palette[View] = view;
+    palette[Primary] = primary;
+    palette[Secondary] = secondary;
+    palette[Tertiary] = tertiary;
+    palette[TitlePrimary] = title_primary;
+    palette[TitleSecondary] = title_secondary;
+    palette[Highlight] = highlight;
+    palette[HighlightInactive] = highlight_inactive;
+    theme.shadow = false;
+    theme.borders = BorderStyle::Simple;
+    Ok(theme)
+}
+
+/// The colours the board is drawn with. These come from the `theme` in the board file, with the
+/// user's own settings from git config on top: `project.theme` for the palette, and
+/// `project.tagColor`, `project.assigneeColor` and `project.columnColor` entries such as
+/// `bug=red`.
+#[derive(Debug)]
+pub struct Colors {
+    theme: Theme,
+    palette: theme::Theme,
+}
+
+impl Colors {
+    pub fn from_config(git_project: &GitProject, config: &Config) -> Result<Colors, Box<dyn Error>> {
+        let mut theme = git_project.theme().clone();
+        for (tag, color) in configured_colors(config, "project.tagColor")? {
+            theme.set_tag_color(tag, color);
+        }
+        for (assignee, color) in configured_colors(config, "project.assigneeColor")? {
+            theme.set_assignee_color(assignee, color);
+        }
+        for (column, color) in configured_colors(config, "project.columnColor")? {
+            theme.set_column_color(column, color);
+        }
+        for color in theme.colors() {
+            parse_color(color)?;
+        }
+
+        // a theme file named in the board file is relative to the repository, and one named in
+        // git config is relative to wherever the user is
+        let workdir = git_project.path().ok().and_then(|path| path.parent().map(Path::to_path_buf)).unwrap_or_default();
+        let palette = match config.get_path("project.theme") {
+            Ok(path) => palette(&path.to_string_lossy(), Path::new(""))?,
+            Err(_) => palette(theme.palette().unwrap_or("default"), &workdir)?,
+        };
+        Ok(Colors { theme, palette })
+    }
+
+    /// The cursive theme for the whole interface.
+    pub fn palette(&self) -> theme::Theme {
+        self.palette.clone()
+    }
+
+    pub fn tag(&self, tag: &str) -> Option<Color> {
+        self.theme.tag_color(tag).and_then(parse)
+    }
+
+    pub fn assignee(&self, assignee: &str) -> Option<Color> {
+        self.theme.assignee_color(assignee).and_then(parse)
+    }
+
+    pub fn column(&self, column: &Column) -> Option<Color> {
+        self.theme.column_color(column).and_then(parse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_hex_codes() {
+        assert_eq!(parse("light blue"), Some(Color::Light(BaseColor::Blue)));
+        assert_eq!(parse("#ff8800"), Some(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(parse("#f80"), Some(Color::Rgb(0xff, 0x88, 0x00)));
+    }
+
+    #[test]
+    fn rejects_what_cursive_would_panic_on() {
+        assert!(parse_color("#ff00").is_err());
+        assert!(parse_color("#").is_err());
+        assert!(parse_color("#gg0000").is_err());
+        assert!(parse_color("ü1").is_err());
+        assert!(parse_color("aüaüa").is_err());
+        assert!(parse_color("0xü1").is_err());
+        assert!(parse_color("mauve").is_err());
+    }
+}
@@ -0,0 +1,65 @@
+use cursive::{Cursive, views::*, view::*};
+use super::super::State;
+
+/// The filter bar, which narrows the board down as you type.
+pub fn text(state: State) -> impl View {
+    let edit = EditView::new()
+        .content(state.filter.borrow().text.clone())
+        .on_edit({ let state = state.clone(); move |s, text, _| {
+            state.filter.borrow_mut().text = text.to_string();
+            state.refresh_board(s);
+        }})
+        .on_submit(|s, _| { s.pop_layer(); })
+        .fixed_width(40);
+
+    Dialog::around(edit)
+        .title("Filter")
+        .button("Done", |s| { s.pop_layer(); })
+        .button("Clear", { let state = state.clone(); move |s: &mut Cursive| {
+            state.filter.borrow_mut().text.clear();
+            s.pop_layer();
+            state.refresh_board(s);
+        }})
+}
+
+/// Picks the tag to filter by.
+pub fn tag(state: State) -> impl View {
+    let git_project = state.git_project.borrow();
+    let project = &git_project.projects()[state.selected_project.get()];
+    let current = state.filter.borrow().tag.clone();
+    let tags: Vec<String> = project.all_tags().into_iter().map(str::to_string).collect();
+
+    let select = tags.iter()
+        .fold(SelectView::new().item("Any tag", None), |sel, tag| sel.item(format!("#{}", tag), Some(tag.clone())))
+        .selected(current.and_then(|current| tags.iter().position(|tag| tag == &current)).map(|i| i + 1).unwrap_or_default())
+        .on_submit({ let state = state.clone(); move |s, tag: &Option<String>| {
+            state.filter.borrow_mut().tag = tag.clone();
+            s.pop_layer();
+            state.refresh_board(s);
+        }});
+
+    Dialog::around(select.scrollable())
+        .title("Filter by tag")
+        .dismiss_button("Cancel")
+}
+
+/// Picks the assignee to filter by.
+pub fn assignee(state: State) -> impl View {
+    let git_project = state.git_project.borrow();
+    let project = &git_project.projects()[state.selected_project.get()];
+    let current = state.filter.borrow().assignee.clone();
+    let assignees: Vec<String> = project.all_assignees().into_iter().map(str::to_string).collect();
+
+    let select = assignees.iter()
+        .fold(SelectView::new().item("Anyone", None), |sel, assignee| sel.item(assignee.clone(), Some(assignee.clone())))
+        .selected(current.and_then(|current| assignees.iter().position(|assignee| assignee == &current)).map(|i| i + 1).unwrap_or_default())
+        .on_submit({ let state = state.clone(); move |s, assignee: &Option<String>| {
+            state.filter.borrow_mut().assignee = assignee.clone();
+            s.pop_layer();
+            state.refresh_board(s);
+        }});
+
+    Dialog::around(select.scrollable())
+        .title("Filter by assignee")
+        .dismiss_button("Cancel")
+}
@@ -9,6 +9,11 @@ pub fn task(state: State, task: Task) -> impl View {
         .map(|tag| TextView::new(format!("#{}", tag)).no_wrap())
         .fold(LinearLayout::vertical(), LinearLayout::child);
 
+    let task_commits = state.task_commits();
+    let commits_list = task_commits.of(project.id(), task.id()).iter()
+        .map(|(id, summary)| TextView::new(format!("{:.7} {}", id, summary)).no_wrap())
+        .fold(LinearLayout::vertical(), LinearLayout::child);
+
     let mut assignee_text = StyledString::plain("Assigned to: ");
     match task.assignee() {
         Some(assignee) if assignee == state.current_user => {
@@ -20,7 +25,7 @@ pub fn task(state: State, task: Task) -> impl View {
     }
 
     let task_description = LinearLayout::vertical()
-        .child(TextView::new(task.name()).effect(Effect::Bold))
+        .child(TextView::new(task.name()).style(Effect::Bold))
         .child(DummyView)
         .child(TextView::new(task.description()))
         .fixed_width(100);
@@ -30,13 +35,16 @@ pub fn task(state: State, task: Task) -> impl View {
         .child(TextView::new(format!("Status:      {}", project.column_of_task(&task).map(|col| col.name()).unwrap_or("Unknown"))))
         .child(LinearLayout::horizontal()
             .child(TextView::new("Tags:        "))
-            .child(tags_list));
+            .child(tags_list))
+        .child(LinearLayout::horizontal()
+            .child(TextView::new("Commits:     "))
+            .child(commits_list));
 
     let task_contents = LinearLayout::horizontal()
-        .child(PaddedView::new((1, 1, 0, 0), task_description))
-        .child(PaddedView::new((4, 1, 0, 0), task_info));
+        .child(PaddedView::lrtb(1, 1, 0, 0, task_description))
+        .child(PaddedView::lrtb(4, 1, 0, 0, task_info));
 
-    Dialog::around(PaddedView::new((0, 0, 1, 0), task_contents))
+    Dialog::around(PaddedView::lrtb(0, 0, 1, 0, task_contents))
         .title(task.id())
         .title_position(HAlign::Left)
         .button("Close", |s| { s.pop_layer(); })
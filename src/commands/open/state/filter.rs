@@ -0,0 +1,41 @@
+use crate::model::*;
+
+/// Narrows down the cards shown on the board.
+#[derive(Default, Clone, Debug)]
+pub struct Filter {
+    /// Text to find in the task's ID, title or description, ignoring case.
+    pub text: String,
+    /// Only show tasks assigned to the current user.
+    pub mine: bool,
+    pub tag: Option<String>,
+    pub assignee: Option<String>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && !self.mine && self.tag.is_none() && self.assignee.is_none()
+    }
+
+    pub fn matches(&self, task: &Task, current_user: &str) -> bool {
+        let text = self.text.to_lowercase();
+        let matches_text = text.is_empty()
+            || task.id().as_ref().to_lowercase().contains(&text)
+            || task.name().to_lowercase().contains(&text)
+            || task.description().to_lowercase().contains(&text);
+        matches_text
+            && (!self.mine || task.assignee() == Some(current_user))
+            && self.tag.as_ref().map(|tag| task.tags().contains(tag)).unwrap_or(true)
+            && self.assignee.as_ref().map(|assignee| task.assignee() == Some(assignee.as_str())).unwrap_or(true)
+    }
+
+    /// A short summary of the filter, for the board's header.
+    pub fn describe(&self) -> String {
+        if self.is_empty() { return String::new() }
+        let mut parts = vec![];
+        if !self.text.is_empty() { parts.push(format!("\"{}\"", self.text)); }
+        if self.mine { parts.push("my tasks".to_string()); }
+        if let Some(tag) = &self.tag { parts.push(format!("#{}", tag)); }
+        if let Some(assignee) = &self.assignee { parts.push(format!("assigned to {}", assignee)); }
+        format!("Showing {}", parts.join(", "))
+    }
+}
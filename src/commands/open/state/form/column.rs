@@ -10,14 +10,14 @@ where F: 'static + Fn(&mut Cursive) {
         .child(TextView::new("Name").fixed_width(12))
         .child(EditView::new()
             .content(column.map(Column::name).unwrap_or_default())
-            .with_id("column-name")
+            .with_name("column-name")
             .full_width());
 
     let description = LinearLayout::horizontal()
         .child(TextView::new("Description").fixed_width(12))
         .child(TextArea::new()
             .content(column.map(Column::description).unwrap_or_default())
-            .with_id("column-description")
+            .with_name("column-description")
             .full_width()
             .min_height(5));
 
@@ -26,10 +26,10 @@ where F: 'static + Fn(&mut Cursive) {
         .child(DummyView)
         .child(description);
 
-    let form_dialog = Dialog::around(PaddedView::new((0, 0, 1, 0), form))
+    let form_dialog = Dialog::around(PaddedView::lrtb(0, 0, 1, 0, form))
         .button("Discard", { let state = state.clone(); let column = column.cloned(); move |s| {
-            let name = s.find_id::<EditView>("column-name").unwrap().get_content().to_string();
-            let description = s.find_id::<TextArea>("column-description").unwrap().get_content().to_string();
+            let name = s.find_name::<EditView>("column-name").unwrap().get_content().to_string();
+            let description = s.find_name::<TextArea>("column-description").unwrap().get_content().to_string();
             if
                 name != column.as_ref().map(Column::name).unwrap_or_default() ||
                 description != column.as_ref().map(Column::description).unwrap_or_default()
@@ -40,8 +40,8 @@ where F: 'static + Fn(&mut Cursive) {
             }
         }})
         .button("Save", { let editing = column.cloned(); move |s| {
-            let name = s.find_id::<EditView>("column-name").unwrap().get_content().to_string();
-            let description = s.find_id::<TextArea>("column-description").unwrap().get_content().to_string();
+            let name = s.find_name::<EditView>("column-name").unwrap().get_content().to_string();
+            let description = s.find_name::<TextArea>("column-description").unwrap().get_content().to_string();
             if name.is_empty() || description.is_empty() {
                 s.add_layer(Dialog::info("Required information is missing"));
                 return;
@@ -0,0 +1,71 @@
+use std::env;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::read_to_string;
+use std::io::Write;
+use std::process::Command;
+use cursive::{Cursive, views::*};
+use git2::Repository;
+use super::State;
+
+#[derive(Debug)]
+struct EditorError(String);
+impl Display for EditorError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "The editor {} did not exit successfully, so the text was not changed.", self.0)
+    }
+}
+impl Error for EditorError {}
+
+/// The editor Git would use: `$GIT_EDITOR`, `core.editor`, `$VISUAL`, or `$EDITOR`, falling back
+/// to vi.
+fn editor() -> String {
+    if let Ok(editor) = env::var("GIT_EDITOR") { return editor }
+    let configured = Repository::open_from_env()
+        .and_then(|repository| repository.config())
+        .and_then(|config| config.get_string("core.editor"));
+    if let Ok(editor) = configured { return editor }
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string())
+}
+
+/// Edits some text in the external editor. The terminal must have been handed over to it first.
+fn edit(text: &str) -> Result<String, Box<dyn Error>> {
+    // the file is created with a name nobody else can guess, and is removed again when dropped
+    let mut file = tempfile::Builder::new().prefix("git-project-").suffix(".md").tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+
+    // the editor may include arguments, so it is run through the shell the same way Git does
+    let editor = editor();
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(&editor)
+        .arg(file.path())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Ok(read_to_string(file.path())?.trim_end().to_string()),
+        _ => Err(Box::new(EditorError(editor))),
+    }
+}
+
+/// A callback that opens the contents of the `TextArea` with the given ID in the external editor,
+/// and puts the result back once the editor exits.
+pub fn edit_externally(state: State, id: &'static str) -> impl Fn(&mut Cursive) + Clone {
+    move |s| {
+        let text = match s.find_name::<TextArea>(id) {
+            Some(text_area) => text_area.get_content().to_string(),
+            None => return,
+        };
+        state.suspend(s, move || {
+            let result = edit(&text);
+            Box::new(move |s: &mut Cursive| match result {
+                Ok(edited) => { s.call_on_name(id, |text_area: &mut TextArea| text_area.set_content(edited)); }
+                Err(error) => s.add_layer(Dialog::info(format!("{}", error))),
+            })
+        });
+    }
+}
@@ -1,3 +1,26 @@
+use cursive::{views::*, view::*};
+use cursive::theme::Effect;
+use super::{Action, State};
+
 pub mod task;
 pub mod column;
 pub mod project;
+mod editor;
+
+/// The description field of a form, which can also be written in the external editor.
+fn description(state: &State, id: &'static str, content: &str) -> impl View {
+    let label = LinearLayout::vertical()
+        .child(TextView::new("Description"));
+    let text_area = TextArea::new()
+        .content(content)
+        .with_name(id)
+        .full_width()
+        .min_height(5);
+
+    let label = label.child(TextView::new(state.keymap.hint(Action::ExternalEditor).map(|key| format!("{} editor", key)).unwrap_or_default()).style(Effect::Italic));
+    let text_area = state.keymap.bind(OnEventView::new(text_area), Action::ExternalEditor, editor::edit_externally(state.clone(), id));
+
+    LinearLayout::horizontal()
+        .child(label.fixed_width(12))
+        .child(text_area)
+}
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 use std::cell::RefCell;
-use cursive::{Cursive, views::*, view::*, event};
-use super::super::State;
+use cursive::{Cursive, views::*, view::*};
+use super::super::{Action, State};
 use crate::model::*;
 
 fn form(state: State, project: Option<&Project>) -> impl View {
@@ -15,20 +15,21 @@ fn form(state: State, project: Option<&Project>) -> impl View {
         .child(TextView::new("Name").fixed_width(12))
         .child(EditView::new()
             .content(project.map(Project::name).unwrap_or_default())
-            .with_id("project-name")
+            .with_name("project-name")
             .full_width());
 
-    let description = LinearLayout::horizontal()
-        .child(TextView::new("Description").fixed_width(12))
-        .child(TextArea::new()
-            .content(project.map(Project::description).unwrap_or_default())
-            .with_id("project-description")
-            .full_width()
-            .min_height(5));
+    let description = super::description(&state, "project-description", project.map(Project::description).unwrap_or_default());
+
+    let task_id_pattern = LinearLayout::horizontal()
+        .child(TextView::new("Task IDs").fixed_width(12))
+        .child(EditView::new()
+            .content(project.map(Project::task_id_pattern).unwrap_or("{project}-{n}"))
+            .with_name("project-task-id-pattern")
+            .full_width());
 
     let edit_column = { let state = state.clone(); let columns = columns.clone(); move |s: &mut Cursive, column: &Column| {
         let form_dialog = super::column::edit(state.clone(), columns.clone(), column.clone(), { let columns = columns.clone(); move |s| {
-            let mut columns_view = s.find_id::<SelectView<Column>>("project-columns").unwrap();
+            let mut columns_view = s.find_name::<SelectView<Column>>("project-columns").unwrap();
             let id = columns_view.selected_id().unwrap();
             let new_column = &columns.borrow()[id];
             columns_view.remove_item(id)(s);
@@ -38,7 +39,7 @@ fn form(state: State, project: Option<&Project>) -> impl View {
     }};
 
     let delete_column = { let state = state.clone(); let columns = columns.clone(); move |s: &mut Cursive| {
-        let mut columns_view = s.find_id::<SelectView<Column>>("project-columns").unwrap();
+        let mut columns_view = s.find_name::<SelectView<Column>>("project-columns").unwrap();
         if let Some(id) = columns_view.selected_id() {
             let mut columns_ref = columns.borrow_mut();
             let column = &columns_ref[id];
@@ -47,7 +48,7 @@ fn form(state: State, project: Option<&Project>) -> impl View {
                 columns_view.remove_item(id)(s);
             } else {
                 state.confirm(s, format!("Are you sure you want to delete {}? Tasks in this column will be lost.", column.name()), { let columns = columns.clone(); move |s| {
-                    let mut columns_view = s.find_id::<SelectView<Column>>("project-columns").unwrap();
+                    let mut columns_view = s.find_name::<SelectView<Column>>("project-columns").unwrap();
                     columns.borrow_mut().remove(id);
                     columns_view.remove_item(id)(s);
                 }});
@@ -58,16 +59,14 @@ fn form(state: State, project: Option<&Project>) -> impl View {
     let columns_list = columns.borrow().iter()
         .fold(SelectView::new(), |sel, col| sel.item(col.name(), col.clone()))
         .on_submit(edit_column)
-        .with_id("project-columns");
+        .with_name("project-columns");
     let columns_container = LinearLayout::horizontal()
         .child(TextView::new("Columns").fixed_width(12))
-        .child(OnEventView::new(columns_list)
-            .on_event(event::Key::Del, delete_column.clone())
-            .on_event(event::Key::Backspace, delete_column))
+        .child(state.keymap.bind(OnEventView::new(columns_list), Action::Delete, delete_column))
         .child(DummyView)
         .child(Button::new("Add Column", { let state = state.clone(); let columns = columns.clone(); move |s| {
             let form_dialog = super::column::new(state.clone(), columns.clone(), { let columns = columns.clone(); move |s| {
-                let mut columns_view = s.find_id::<SelectView<Column>>("project-columns").unwrap();
+                let mut columns_view = s.find_name::<SelectView<Column>>("project-columns").unwrap();
                 let new_column = columns.borrow().last().unwrap().clone();
                 columns_view.add_item(new_column.name().to_string(), new_column);
             }});
@@ -79,12 +78,14 @@ fn form(state: State, project: Option<&Project>) -> impl View {
         .child(DummyView)
         .child(description)
         .child(DummyView)
+        .child(task_id_pattern)
+        .child(DummyView)
         .child(columns_container);
 
-    let form_dialog = Dialog::around(PaddedView::new((0, 0, 1, 0), form))
+    let form_dialog = Dialog::around(PaddedView::lrtb(0, 0, 1, 0, form))
         .button("Discard", { let state = state.clone(); let project = project.cloned(); move |s| {
-            let name = s.find_id::<EditView>("project-name").unwrap().get_content().to_string();
-            let description = s.find_id::<TextArea>("project-description").unwrap().get_content().to_string();
+            let name = s.find_name::<EditView>("project-name").unwrap().get_content().to_string();
+            let description = s.find_name::<TextArea>("project-description").unwrap().get_content().to_string();
             if
                 name != project.as_ref().map(Project::name).unwrap_or_default() ||
                 description != project.as_ref().map(Project::description).unwrap_or_default()
@@ -95,15 +96,22 @@ fn form(state: State, project: Option<&Project>) -> impl View {
             }
         }})
         .button("Save", { let state = state.clone(); let editing = project.cloned(); move |s| {
-            let name = s.find_id::<EditView>("project-name").unwrap().get_content().to_string();
-            let description = s.find_id::<TextArea>("project-description").unwrap().get_content().to_string();
-            if name.is_empty() || description.is_empty() {
+            let name = s.find_name::<EditView>("project-name").unwrap().get_content().to_string();
+            let description = s.find_name::<TextArea>("project-description").unwrap().get_content().to_string();
+            let task_id_pattern = s.find_name::<EditView>("project-task-id-pattern").unwrap().get_content().trim().to_string();
+            if name.is_empty() || description.is_empty() || task_id_pattern.is_empty() {
                 s.add_layer(Dialog::info("Required information is missing"));
                 return;
             }
+            let id = editing.as_ref().map(|editing| editing.id().clone()).unwrap_or_else(|| state.git_project.borrow().generate_project_id(&name));
+            let empty_project = Project::new(&id)
+                .name(&name)
+                .description(description)
+                .task_id_pattern(task_id_pattern)
+                .task_counter(editing.as_ref().map(Project::task_counter).unwrap_or_default());
             let project_with_columns = columns.borrow().iter()
                 .cloned()
-                .fold(Project::new(&name).description(description), ProjectBuilder::column);
+                .fold(empty_project, ProjectBuilder::column);
             let project = editing.as_ref()
                 .map(|editing| editing
                     .tasks()
@@ -122,14 +130,16 @@ fn form(state: State, project: Option<&Project>) -> impl View {
                 .unwrap();
 
             if let Some(editing) = editing.as_ref() {
-                state.git_project
-                    .borrow_mut()
-                    .replace_project(editing.name(), project);
+                state.change(format!("edit project {}", editing.name()), |git_project| {
+                    git_project.replace_project(editing.name(), project);
+                    true
+                });
                 s.pop_layer();
                 state.reload(s);
             } else {
-                let success = state.git_project.borrow_mut()
-                    .add_project(project);
+                let success = state.change(format!("add project {}", name), |git_project| {
+                    git_project.add_project(project)
+                });
                 if success {
                     s.pop_layer();
                     state.reload(s);
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 use std::cell::RefCell;
-use cursive::{Cursive, views::*, view::*, event};
-use super::super::State;
+use cursive::{Cursive, views::*, view::*};
+use super::super::{Action, State};
 use crate::model::*;
 
 fn form(state: State, task: Option<&Task>) -> impl View {
@@ -13,14 +13,15 @@ fn form(state: State, task: Option<&Task>) -> impl View {
         .child(TextView::new("Task ID").fixed_width(12))
         .child(EditView::new()
             .content(task.map(Task::id).map(Into::<String>::into).unwrap_or_default())
-            .with_id("new-task-id")
-            .full_width());
+            .with_name("new-task-id")
+            .full_width())
+        .child(TextView::new(if task.is_none() { " (leave blank to generate)" } else { "" }));
 
     let title = LinearLayout::horizontal()
         .child(TextView::new("Title").fixed_width(12))
         .child(EditView::new()
             .content(task.map(Task::name).unwrap_or_default())
-            .with_id("new-task-title")
+            .with_name("new-task-title")
             .full_width());
 
     let assignees = project.all_assignees();
@@ -31,7 +32,7 @@ fn form(state: State, task: Option<&Task>) -> impl View {
             .selected(task.and_then(Task::assignee).and_then(|name| assignees.iter().position(|a| a == &name)).map(|x| x + 1).unwrap_or_default())
             .popup()
             .autojump()
-            .with_id("new-task-assignee"));
+            .with_name("new-task-assignee"));
 
     let column = LinearLayout::horizontal()
         .child(TextView::new("Column").fixed_width(12))
@@ -42,15 +43,9 @@ fn form(state: State, task: Option<&Task>) -> impl View {
             .selected(initial_column.unwrap_or_default())
             .popup()
             .autojump()
-            .with_id("new-task-column"));
+            .with_name("new-task-column"));
 
-    let description = LinearLayout::horizontal()
-        .child(TextView::new("Description").fixed_width(12))
-        .child(TextArea::new()
-            .content(task.map(Task::description).unwrap_or_default())
-            .with_id("new-task-description")
-            .full_width()
-            .min_height(5));
+    let description = super::description(&state, "new-task-description", task.map(Task::description).unwrap_or_default());
 
     let initial_tags: Vec<String> = task.map(Task::tags).map(|tags| tags.into_iter().cloned().collect()).unwrap_or_default();
     let selected_tags = Rc::new(RefCell::new(initial_tags.clone()));
@@ -60,20 +55,18 @@ fn form(state: State, task: Option<&Task>) -> impl View {
         // 1.  Remove from selected tags
         // 2.  Remove from selected list
         // 3.  Add to suggestions
-        let mut selected_tags_view = s.find_id::<SelectView>("selected-tags").unwrap();
+        let mut selected_tags_view = s.find_name::<SelectView>("selected-tags").unwrap();
         if let Some(id) = selected_tags_view.selected_id() {
             let tag = selected_tags.borrow_mut().remove(id);
             selected_tags_view.remove_item(id)(s);
-            let mut suggested_tags_view = s.find_id::<SelectView>("suggested-tags").unwrap();
+            let mut suggested_tags_view = s.find_name::<SelectView>("suggested-tags").unwrap();
             suggested_tags_view.add_item_str(tag);
         }
     }};
 
     let tags = LinearLayout::horizontal()
         .child(LinearLayout::vertical().child(DummyView).child(TextView::new("Tags").fixed_width(12)))
-        .child(Panel::new(OnEventView::new(initial_tags.iter().fold(SelectView::<String>::new(), SelectView::item_str).with_id("selected-tags"))
-                .on_event(event::Key::Del, delete_tag.clone())
-                .on_event(event::Key::Backspace, delete_tag)
+        .child(Panel::new(state.keymap.bind(OnEventView::new(initial_tags.iter().fold(SelectView::<String>::new(), SelectView::item_str).with_name("selected-tags")), Action::Delete, delete_tag)
                 .min_size((10, 2))))
         .child(LinearLayout::vertical()
             .child(DummyView)
@@ -86,16 +79,16 @@ fn form(state: State, task: Option<&Task>) -> impl View {
                     // 2.  Add to selected list
                     // 3.  Remove from suggestions
                     if tag.is_empty() { return }
-                    let mut selected_tags_view = s.find_id::<SelectView>("selected-tags").unwrap();
+                    let mut selected_tags_view = s.find_name::<SelectView>("selected-tags").unwrap();
                     selected_tags.borrow_mut().push(tag.clone());
                     selected_tags_view.add_item_str(tag);
-                    let mut suggested_tags_view = s.find_id::<SelectView>("suggested-tags").unwrap();
+                    let mut suggested_tags_view = s.find_name::<SelectView>("suggested-tags").unwrap();
                     let id = suggested_tags_view.selected_id().unwrap();
                     suggested_tags_view.remove_item(id);
                     suggested_tags_view.set_selection(0);
                 }})
                 .popup()
-                .with_id("suggested-tags"))
+                .with_name("suggested-tags"))
             .child(EditView::new()
                 .on_submit_mut({ let selected_tags = selected_tags.clone(); move |s, tag| {
                     // add a newly invented tag
@@ -108,19 +101,19 @@ fn form(state: State, task: Option<&Task>) -> impl View {
                     if selected_tags.borrow().contains(&tag.to_string()) { return }
                     selected_tags.borrow_mut().push(tag.to_string());
 
-                    let mut selected_tags_view = s.find_id::<SelectView>("selected-tags").unwrap();
+                    let mut selected_tags_view = s.find_name::<SelectView>("selected-tags").unwrap();
                     selected_tags_view.add_item_str(tag);
 
-                    let mut suggested_tags_view = s.find_id::<SelectView>("suggested-tags").unwrap();
+                    let mut suggested_tags_view = s.find_name::<SelectView>("suggested-tags").unwrap();
                     let position = suggested_tags_view.iter().position(|(_, suggestion)| suggestion == tag);
                     if let Some(position) = position {
                         suggested_tags_view.remove_item(position)(s);
                     }
 
-                    let mut edit_view = s.find_id::<EditView>("tag-input").unwrap();
+                    let mut edit_view = s.find_name::<EditView>("tag-input").unwrap();
                     edit_view.set_content("")(s);
                 }})
-                .with_id("tag-input")
+                .with_name("tag-input")
                 .fixed_width(20)));
 
     let form = LinearLayout::vertical()
@@ -136,13 +129,13 @@ fn form(state: State, task: Option<&Task>) -> impl View {
         .child(DummyView)
         .child(tags);
 
-    let form_dialog = Dialog::around(PaddedView::new((0, 0, 1, 0), form))
+    let form_dialog = Dialog::around(PaddedView::lrtb(0, 0, 1, 0, form))
         .button("Discard", { let state = state.clone(); let selected_tags = selected_tags.clone(); let task = task.cloned(); move |s| {
-            let id = s.find_id::<EditView>("new-task-id").unwrap().get_content().to_string();
-            let title = s.find_id::<EditView>("new-task-title").unwrap().get_content().to_string();
-            let assignee = s.find_id::<SelectView<Option<String>>>("new-task-assignee").unwrap().selection().and_then(|rc| (*rc).clone());
-            let column = *s.find_id::<SelectView<usize>>("new-task-column").unwrap().selection().unwrap();
-            let description = s.find_id::<TextArea>("new-task-description").unwrap().get_content().to_string();
+            let id = s.find_name::<EditView>("new-task-id").unwrap().get_content().to_string();
+            let title = s.find_name::<EditView>("new-task-title").unwrap().get_content().to_string();
+            let assignee = s.find_name::<SelectView<Option<String>>>("new-task-assignee").unwrap().selection().and_then(|rc| (*rc).clone());
+            let column = *s.find_name::<SelectView<usize>>("new-task-column").unwrap().selection().unwrap();
+            let description = s.find_name::<TextArea>("new-task-description").unwrap().get_content().to_string();
             if
                 id != task.as_ref().map(Task::id).map(Into::<String>::into).unwrap_or_default() ||
                 title != task.as_ref().map(Task::name).unwrap_or_default() ||
@@ -157,37 +150,50 @@ fn form(state: State, task: Option<&Task>) -> impl View {
             }
         }})
         .button("Save", { let state = state.clone(); let editing = task.cloned(); move |s| {
-            let id = s.find_id::<EditView>("new-task-id").unwrap().get_content().trim().to_string();
-            let title = s.find_id::<EditView>("new-task-title").unwrap().get_content().trim().to_string();
-            let assignee = s.find_id::<SelectView<Option<String>>>("new-task-assignee").unwrap().selection().and_then(|rc| (*rc).clone());
-            let column = *s.find_id::<SelectView<usize>>("new-task-column").unwrap().selection().unwrap();
-            let description = s.find_id::<TextArea>("new-task-description").unwrap().get_content().trim().to_string();
-            if id.is_empty() || title.is_empty() || description.is_empty() {
+            let id = s.find_name::<EditView>("new-task-id").unwrap().get_content().trim().to_string();
+            let title = s.find_name::<EditView>("new-task-title").unwrap().get_content().trim().to_string();
+            let assignee = s.find_name::<SelectView<Option<String>>>("new-task-assignee").unwrap().selection().and_then(|rc| (*rc).clone());
+            let column = *s.find_name::<SelectView<usize>>("new-task-column").unwrap().selection().unwrap();
+            let description = s.find_name::<TextArea>("new-task-description").unwrap().get_content().trim().to_string();
+            if (id.is_empty() && editing.is_some()) || title.is_empty() || description.is_empty() {
                 s.add_layer(Dialog::info("Required information is missing"));
                 return;
             }
-            let mut task = Task::new(&id).name(title).description(description);
-            if let Some(assignee) = assignee { task = task.assignee(assignee); }
-
-            let task = selected_tags.borrow().iter().fold(task, |task, tag| task.tag(tag)).build().unwrap();
-
+            // a generated ID is only taken from the project's counter once the task is added, so
+            // that undoing the addition gives the ID back
+            let id = if id.is_empty() { None } else { Some(Id::new(id)) };
+            if id.as_ref().is_some_and(|id| !id.is_valid()) {
+                s.add_layer(Dialog::info("Task IDs cannot contain whitespace, square brackets or @"));
+                return;
+            }
+            let tags = selected_tags.borrow().clone();
+            let build = |id: &Id| {
+                let mut task = Task::new(id).name(&title).description(&description);
+                if let Some(assignee) = assignee.as_ref() { task = task.assignee(assignee); }
+                tags.iter().fold(task, |task, tag| task.tag(tag)).build().unwrap()
+            };
+
+            let selected_project = state.selected_project.get();
             if let Some(editing) = editing.as_ref() {
-                state.git_project
-                    .borrow_mut()
-                    .projects_mut()[state.selected_project.get()]
-                    .replace_task(editing.id(), task, Some(column));
+                let id = id.unwrap();
+                state.change(format!("edit task {}", editing.id()), |git_project| {
+                    git_project.projects_mut()[selected_project].replace_task(editing.id(), build(&id), Some(column));
+                    true
+                });
                 s.pop_layer();
                 state.reload(s);
             } else {
-                let success = state.git_project
-                    .borrow_mut()
-                    .projects_mut()[state.selected_project.get()]
-                    .add_task(task, column);
+                let description = format!("add task {}", id.as_ref().map(Id::to_string).unwrap_or_else(|| title.clone()));
+                let success = state.change(description, |git_project| {
+                    let project = &mut git_project.projects_mut()[selected_project];
+                    let id = id.clone().unwrap_or_else(|| project.generate_task_id(&title));
+                    project.add_task(build(&id), column)
+                });
                 if success {
                     s.pop_layer();
                     state.reload(s);
                 } else {
-                    s.add_layer(Dialog::info(format!("A task with ID {} already exists", id)));
+                    s.add_layer(Dialog::info(format!("A task with ID {} already exists", id.unwrap())));
                 }
             }
         }});
@@ -1,8 +1,10 @@
 use cursive::{Cursive, views::*};
+use super::Keymap;
 
-pub fn show(siv: &mut Cursive) {
-    let help_text = LinearLayout::vertical()
-        .child(TextView::new("?: Show this help"));
+/// Lists what each key does, as set up in the keymap.
+pub fn show(keymap: &Keymap, siv: &mut Cursive) {
+    let help_text = keymap.help().into_iter()
+        .fold(LinearLayout::vertical(), |layout, line| layout.child(TextView::new(line)));
 
     let dialog = Dialog::around(help_text)
         .title("Help")
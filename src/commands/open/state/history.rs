@@ -0,0 +1,104 @@
+use crate::model::*;
+
+/// How many changes can be undone.
+const HISTORY_LIMIT: usize = 100;
+
+/// A change made to the project, along with the project as it was on the other side of it.
+#[derive(Debug)]
+struct Change {
+    /// Identifies the state of the project after the change was made.
+    id: usize,
+    description: String,
+    git_project: GitProject,
+}
+
+/// The changes made during this session, which can be undone and redone.
+#[derive(Default, Debug)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+    /// How many changes have been recorded, which gives each one its ID.
+    recorded: usize,
+}
+
+impl History {
+    /// Records a change, given the project as it was before the change was made.
+    pub fn record(&mut self, description: String, before: GitProject) {
+        self.recorded += 1;
+        self.undo.push(Change { id: self.recorded, description, git_project: before });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    /// Undoes the last change, given the current project, returning the project as it was before.
+    pub fn undo(&mut self, current: GitProject) -> Option<GitProject> {
+        let change = self.undo.pop()?;
+        self.redo.push(Change { id: change.id, description: change.description, git_project: current });
+        Some(change.git_project)
+    }
+
+    /// Redoes the last undone change, given the current project, returning the project as it was
+    /// after the change.
+    pub fn redo(&mut self, current: GitProject) -> Option<GitProject> {
+        let change = self.redo.pop()?;
+        self.undo.push(Change { id: change.id, description: change.description, git_project: current });
+        Some(change.git_project)
+    }
+
+    /// Identifies the current state of the project: this is the same whenever changes are undone
+    /// or redone back to the same state, and different for every other state. It is 0 before any
+    /// changes have been made.
+    pub fn position(&self) -> usize {
+        self.undo.last().map(|change| change.id).unwrap_or(0)
+    }
+
+    pub fn undo_description(&self) -> Option<&str> {
+        self.undo.last().map(|change| change.description.as_str())
+    }
+
+    pub fn redo_description(&self) -> Option<&str> {
+        self.redo.last().map(|change| change.description.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(name: &str) -> GitProject {
+        let project = Project::new("p").name(name).description("").build().unwrap();
+        GitProject::new().project(project).build().unwrap()
+    }
+
+    #[test]
+    fn undoing_and_redoing_returns_to_the_same_position() {
+        let mut history = History::default();
+        assert_eq!(history.position(), 0);
+        history.record("first".to_string(), board("a"));
+        let first = history.position();
+        history.record("second".to_string(), board("b"));
+        let second = history.position();
+        assert_ne!(first, second);
+
+        history.undo(board("c"));
+        assert_eq!(history.position(), first);
+        history.undo(board("b"));
+        assert_eq!(history.position(), 0);
+        history.redo(board("a"));
+        history.redo(board("b"));
+        assert_eq!(history.position(), second);
+    }
+
+    #[test]
+    fn new_changes_get_new_positions() {
+        let mut history = History::default();
+        history.record("first".to_string(), board("a"));
+        let first = history.position();
+        history.undo(board("b"));
+        history.record("other".to_string(), board("a"));
+        assert_ne!(history.position(), first);
+        assert_ne!(history.position(), 0);
+    }
+}
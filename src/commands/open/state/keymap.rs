@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use cursive::{Cursive, views::OnEventView, view::View};
+use cursive::event::{Event, Key};
+use git2::Config;
+
+#[derive(Debug)]
+struct KeymapError(String);
+impl Display for KeymapError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+impl Error for KeymapError {}
+
+/// Something that can be done on the board with a key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Help,
+    Menu,
+    Undo,
+    Redo,
+    Filter,
+    FilterTag,
+    FilterAssignee,
+    MyTasks,
+    MoveRight,
+    MoveLeft,
+    MoveUp,
+    MoveDown,
+    Delete,
+    ExternalEditor,
+}
+
+impl Action {
+    /// Every action, in the order they are listed in the help.
+    const ALL: [Action; 14] = [
+        Action::Help,
+        Action::Menu,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::Delete,
+        Action::Filter,
+        Action::MyTasks,
+        Action::FilterTag,
+        Action::FilterAssignee,
+        Action::Undo,
+        Action::Redo,
+        Action::ExternalEditor,
+    ];
+
+    /// The name of the action in `project.keys.<name>`.
+    fn name(self) -> &'static str {
+        match self {
+            Action::Help => "help",
+            Action::Menu => "menu",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Filter => "filter",
+            Action::FilterTag => "filter-tag",
+            Action::FilterAssignee => "filter-assignee",
+            Action::MyTasks => "my-tasks",
+            Action::MoveRight => "move-right",
+            Action::MoveLeft => "move-left",
+            Action::MoveUp => "move-up",
+            Action::MoveDown => "move-down",
+            Action::Delete => "delete",
+            Action::ExternalEditor => "external-editor",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Action::Help => "Show this help",
+            Action::Menu => "Open the menu",
+            Action::Undo => "Undo the last change",
+            Action::Redo => "Redo the last undone change",
+            Action::Filter => "Filter tasks by their ID, title or description",
+            Action::FilterTag => "Filter tasks by tag",
+            Action::FilterAssignee => "Filter tasks by assignee",
+            Action::MyTasks => "Show only tasks assigned to you, or all tasks",
+            Action::MoveRight => "Move the selected task to the next column",
+            Action::MoveLeft => "Move the selected task to the previous column",
+            Action::MoveUp => "Move the selected task up its column",
+            Action::MoveDown => "Move the selected task down its column",
+            Action::Delete => "Delete the selected task, project, column or tag",
+            Action::ExternalEditor => "Edit a description in your editor",
+        }
+    }
+
+    /// The keys bound to the action in one of the built in keymaps.
+    fn default_keys(self, preset: Preset) -> &'static str {
+        match (preset, self) {
+            (_, Action::Menu) => "Esc",
+            (_, Action::Delete) => "Del Backspace",
+            (_, Action::MyTasks) => "m",
+            (_, Action::FilterTag) => "t",
+            (_, Action::FilterAssignee) => "a",
+            (_, Action::ExternalEditor) => "Ctrl-e",
+
+            (Preset::Vim, Action::Help) => "?",
+            (Preset::Vim, Action::Undo) => "u",
+            (Preset::Vim, Action::Redo) => "Ctrl-r",
+            (Preset::Vim, Action::Filter) => "/",
+            (Preset::Vim, Action::MoveRight) => "l L",
+            (Preset::Vim, Action::MoveLeft) => "h H",
+            (Preset::Vim, Action::MoveUp) => "K",
+            (Preset::Vim, Action::MoveDown) => "J",
+
+            (Preset::Arrows, Action::Help) => "? F1",
+            (Preset::Arrows, Action::Undo) => "Ctrl-z",
+            (Preset::Arrows, Action::Redo) => "Ctrl-y",
+            (Preset::Arrows, Action::Filter) => "Ctrl-f /",
+            (Preset::Arrows, Action::MoveRight) => "Shift-Right",
+            (Preset::Arrows, Action::MoveLeft) => "Shift-Left",
+            (Preset::Arrows, Action::MoveUp) => "Shift-Up",
+            (Preset::Arrows, Action::MoveDown) => "Shift-Down",
+
+            (Preset::Emacs, Action::Help) => "? F1",
+            (Preset::Emacs, Action::Undo) => "Ctrl-z",
+            (Preset::Emacs, Action::Redo) => "Ctrl-y",
+            (Preset::Emacs, Action::Filter) => "Ctrl-s",
+            (Preset::Emacs, Action::MoveRight) => "Ctrl-f",
+            (Preset::Emacs, Action::MoveLeft) => "Ctrl-b",
+            (Preset::Emacs, Action::MoveUp) => "Ctrl-p",
+            (Preset::Emacs, Action::MoveDown) => "Ctrl-n",
+        }
+    }
+}
+
+/// The built in keymaps, chosen with `project.keymap`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Preset {
+    Vim,
+    Arrows,
+    Emacs,
+}
+
+impl Preset {
+    fn parse(name: &str) -> Result<Preset, Box<dyn Error>> {
+        match name {
+            "vim" => Ok(Preset::Vim),
+            "arrows" => Ok(Preset::Arrows),
+            "emacs" => Ok(Preset::Emacs),
+            _ => Err(Box::new(KeymapError(format!("Unknown keymap \"{}\" in project.keymap. Use vim, arrows or emacs.", name)))),
+        }
+    }
+}
+
+const KEY_NAMES: [(&str, Key); 26] = [
+    ("Enter", Key::Enter),
+    ("Tab", Key::Tab),
+    ("Backspace", Key::Backspace),
+    ("Esc", Key::Esc),
+    ("Left", Key::Left),
+    ("Right", Key::Right),
+    ("Up", Key::Up),
+    ("Down", Key::Down),
+    ("Ins", Key::Ins),
+    ("Del", Key::Del),
+    ("Home", Key::Home),
+    ("End", Key::End),
+    ("PageUp", Key::PageUp),
+    ("PageDown", Key::PageDown),
+    ("F1", Key::F1),
+    ("F2", Key::F2),
+    ("F3", Key::F3),
+    ("F4", Key::F4),
+    ("F5", Key::F5),
+    ("F6", Key::F6),
+    ("F7", Key::F7),
+    ("F8", Key::F8),
+    ("F9", Key::F9),
+    ("F10", Key::F10),
+    ("F11", Key::F11),
+    ("F12", Key::F12),
+];
+
+fn parse_key(name: &str) -> Option<Key> {
+    KEY_NAMES.iter().find(|(key_name, _)| key_name.eq_ignore_ascii_case(name)).map(|(_, key)| *key)
+}
+
+fn key_name(key: Key) -> &'static str {
+    KEY_NAMES.iter().find(|(_, named)| *named == key).map(|(name, _)| *name).unwrap_or("?")
+}
+
+fn single_char(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Some(ch),
+        _ => None,
+    }
+}
+
+/// Parses a key such as `a`, `Ctrl-r`, `Shift-Left` or `Del`.
+fn parse_event(text: &str) -> Option<Event> {
+    if let Some(ch) = single_char(text) {
+        return Some(Event::Char(ch));
+    }
+    if let Some(key) = parse_key(text) {
+        return Some(Event::Key(key));
+    }
+    let mut parts = text.splitn(2, '-');
+    let (modifier, key) = (parts.next()?, parts.next()?);
+    match (modifier.to_ascii_lowercase().as_str(), single_char(key)) {
+        // Ctrl-h, Ctrl-i, Ctrl-j and Ctrl-m reach us as Backspace, Tab and Enter, and Ctrl-c quits
+        ("ctrl", Some(ch)) if ch.is_ascii_alphabetic() && !"hHiIjJmMcC".contains(ch) => Some(Event::CtrlChar(ch.to_ascii_lowercase())),
+        ("alt", Some(ch)) => Some(Event::AltChar(ch)),
+        ("ctrl", None) => parse_key(key).map(Event::Ctrl),
+        ("shift", None) => parse_key(key).map(Event::Shift),
+        ("alt", None) => parse_key(key).map(Event::Alt),
+        _ => None,
+    }
+}
+
+fn describe_event(event: &Event) -> String {
+    match event {
+        Event::Char(ch) => ch.to_string(),
+        Event::CtrlChar(ch) => format!("Ctrl-{}", ch),
+        Event::AltChar(ch) => format!("Alt-{}", ch),
+        Event::Key(key) => key_name(*key).to_string(),
+        Event::Ctrl(key) => format!("Ctrl-{}", key_name(*key)),
+        Event::Shift(key) => format!("Shift-{}", key_name(*key)),
+        Event::Alt(key) => format!("Alt-{}", key_name(*key)),
+        _ => "?".to_string(),
+    }
+}
+
+/// Parses a space separated list of keys.
+fn parse_events(text: &str, setting: &str) -> Result<Vec<Event>, Box<dyn Error>> {
+    text.split_whitespace()
+        .map(|key| parse_event(key).ok_or_else(|| -> Box<dyn Error> {
+            Box::new(KeymapError(format!("Unknown key \"{}\" in {}. Use keys such as a, Ctrl-r, Shift-Left or Del.", key, setting)))
+        }))
+        .collect()
+}
+
+/// Which keys do what on the board.
+///
+/// This starts from one of the built in keymaps, chosen with `project.keymap` (`vim`, the default,
+/// `arrows` or `emacs`), and then takes any keys set for individual actions with
+/// `project.keys.<action>`, e.g. `git config --global project.keys.undo "u Ctrl-z"`. Setting an
+/// action to an empty string unbinds it.
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<Event>>,
+}
+
+impl Keymap {
+    pub fn from_config(config: &Config) -> Result<Keymap, Box<dyn Error>> {
+        let preset = match config.get_string("project.keymap") {
+            Ok(name) => Preset::parse(&name)?,
+            Err(_) => Preset::Vim,
+        };
+        let mut bindings = HashMap::new();
+        for action in Action::ALL.iter() {
+            let setting = format!("project.keys.{}", action.name());
+            let events = match config.get_string(&setting) {
+                Ok(keys) => parse_events(&keys, &setting)?,
+                Err(_) => parse_events(action.default_keys(preset), &setting)?,
+            };
+            bindings.insert(*action, events);
+        }
+        Ok(Keymap { bindings })
+    }
+
+    pub fn keys(&self, action: Action) -> &[Event] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Registers the callback for each of the keys bound to the action.
+    pub fn bind<T, F>(&self, view: OnEventView<T>, action: Action, callback: F) -> OnEventView<T>
+    where T: View, F: 'static + Fn(&mut Cursive) + Clone {
+        self.keys(action).iter().fold(view, |view, event| view.on_event(event.clone(), callback.clone()))
+    }
+
+    /// The first key bound to the action, for hints next to the views it works in.
+    pub fn hint(&self, action: Action) -> Option<String> {
+        self.keys(action).first().map(describe_event)
+    }
+
+    /// A line of help for each bound action, listing its keys.
+    pub fn help(&self) -> Vec<String> {
+        Action::ALL.iter()
+            .filter(|action| !self.keys(**action).is_empty())
+            .map(|action| {
+                let keys: Vec<String> = self.keys(*action).iter().map(describe_event).collect();
+                format!("{}: {}", keys.join("/"), action.description())
+            })
+            .collect()
+    }
+}
@@ -1,44 +1,109 @@
 use std::cell::{Cell, RefCell};
 use std::error::Error;
-use std::fmt::Display;
+use std::fmt::{self, Debug, Display, Formatter};
 use std::rc::Rc;
-use cursive::Cursive;
-use cursive::{menu::*, views::*, event};
+use std::thread;
+use std::time::Duration;
+use cursive::{backend::Backend, Cursive};
+use cursive::{menu::*, views::*};
+use git2::Repository;
+use crate::commands::TaskCommits;
 use crate::model::*;
 
 mod board;
+mod colors;
 mod dialog;
+mod filter;
 mod help;
+mod history;
+mod keymap;
 mod form;
 
+use filter::Filter;
+use history::History;
+use keymap::Action;
+pub use keymap::Keymap;
+pub use colors::Colors;
+
+/// How often the project file is checked for changes made outside of the board.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[cfg(not(any(feature = "termion-backend", feature = "crossterm-backend", feature = "pancurses-backend")))]
+compile_error!("git-project needs one of the termion-backend, crossterm-backend or pancurses-backend features.");
+
+/// Starts the terminal backend git-project was built with. If more than one was chosen, the one
+/// other than the default crossterm is preferred, pancurses first.
+fn backend() -> Result<Box<dyn Backend>, Box<dyn Error>> {
+    #[cfg(feature = "pancurses-backend")]
+    return Ok(cursive::backends::curses::pan::Backend::init()?);
+    #[cfg(all(feature = "termion-backend", not(feature = "pancurses-backend")))]
+    return Ok(cursive::backends::termion::Backend::init()?);
+    #[cfg(all(feature = "crossterm-backend", not(any(feature = "termion-backend", feature = "pancurses-backend"))))]
+    return Ok(cursive::backends::crossterm::Backend::init()?);
+}
+
+/// What to do once the terminal is back from another program.
+type Resume = Box<dyn FnOnce(&mut Cursive)>;
+
+/// A program waiting for the board to stop so it can have the terminal, see `State::suspend`.
+#[derive(Default)]
+struct Suspended(Option<Box<dyn FnOnce() -> Resume>>);
+
+impl Debug for Suspended {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_tuple("Suspended").field(&self.0.is_some()).finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct State {
     git_project: Rc<RefCell<GitProject>>,
+    saved_project: Rc<RefCell<GitProject>>,
     selected_project: Rc<Cell<usize>>,
-    unsaved_changes: Rc<Cell<bool>>,
+    /// The position in the history at which the project was last loaded or saved.
+    saved_position: Rc<Cell<usize>>,
+    filter: Rc<RefCell<Filter>>,
+    history: Rc<RefCell<History>>,
+    /// The revision on disk the user chose to keep their changes over, so they are not asked again.
+    dismissed_revision: Rc<Cell<Option<u64>>>,
+    keymap: Rc<Keymap>,
+    colors: Rc<Colors>,
+    suspended: Rc<RefCell<Suspended>>,
+    /// The commits that changed each task, read when first needed, and again once the project
+    /// file changes on disk, as it does when a commit changes the board.
+    task_commits: Rc<RefCell<Option<Rc<TaskCommits>>>>,
     current_user: String,
 }
 
 impl State {
-    pub fn new(git_project: GitProject, current_user: String) -> Self {
+    pub fn new(git_project: GitProject, current_user: String, keymap: Keymap, colors: Colors) -> Self {
         Self {
+            saved_project: Rc::new(RefCell::new(git_project.clone())),
             git_project: Rc::new(RefCell::new(git_project)),
             selected_project: Rc::new(Cell::new(0)),
-            unsaved_changes: Rc::new(Cell::new(false)),
+            saved_position: Rc::new(Cell::new(0)),
+            filter: Rc::new(RefCell::new(Filter::default())),
+            history: Rc::new(RefCell::new(History::default())),
+            dismissed_revision: Rc::new(Cell::new(None)),
+            keymap: Rc::new(keymap),
+            colors: Rc::new(colors),
+            suspended: Rc::new(RefCell::new(Suspended::default())),
+            task_commits: Rc::new(RefCell::new(None)),
             current_user,
         }
     }
 
     pub fn run(self) -> Result<(), Box<dyn Error>> {
-        let mut siv = Cursive::pancurses().unwrap();
+        let mut siv = Cursive::new();
         siv.set_autohide_menu(false);
-        let file_menu = MenuTree::new()
+        siv.set_theme(self.colors.palette());
+        let file_menu = Tree::new()
             .leaf("New Task", { let state = self.clone(); move |s| { state.new_task(s) }})
             .leaf("New Project", { let state = self.clone(); move |s| { state.new_project(s) }})
-            .leaf("Save", { let state = self.clone(); move |s| { state.save(s); }})
+            .leaf("Save", { let state = self.clone(); move |s| { state.save(s, |_| {}); }})
             .delimiter()
             .leaf("Quit", { let state = self.clone(); move |s| { state.quit(s); }});
-        let project_menu = MenuTree::new()
+        let project_menu = Tree::new()
             .leaf("Edit Project", { let state = self.clone(); move |s| {
                 if let Some(project) = state.git_project.borrow().projects().iter().skip(state.selected_project.get()).next() {
                     state.edit_project(project.clone(), s);
@@ -48,34 +113,280 @@ impl State {
             }});
         siv.menubar()
             .add_subtree("File", file_menu)
+            .add_subtree("Edit", Tree::new())
             .add_subtree("Project", project_menu);
 
         self.reload(&mut siv);
+        self.watch(&mut siv);
 
-        siv.run();
-        Ok(())
+        loop {
+            siv.runner(backend()?).run();
+            // the runner has been dropped along with its backend, which gives the terminal back
+            let program = match self.suspended.borrow_mut().0.take() {
+                Some(program) => program,
+                None => return Ok(()),
+            };
+            let resume = program();
+            resume(&mut siv);
+        }
+    }
+
+    /// Hands the terminal over to another program, such as an editor. `program` runs once the
+    /// board is no longer drawn, and returns what to do once the board is back.
+    fn suspend<P>(&self, siv: &mut Cursive, program: P)
+    where P: 'static + FnOnce() -> Resume {
+        // curses can be left and come back to without stopping the backend
+        #[cfg(feature = "pancurses-backend")]
+        {
+            use std::io::{stdout, Write};
+
+            print!("\x1B[?1002l");
+            stdout().flush().ok();
+            pancurses::endwin();
+            let resume = program();
+            print!("\x1B[?1002h");
+            stdout().flush().ok();
+            siv.clear();
+            resume(siv);
+        }
+        // the other backends stop the board, and `run` starts it again once the program is done
+        #[cfg(not(feature = "pancurses-backend"))]
+        {
+            self.suspended.borrow_mut().0 = Some(Box::new(program));
+            siv.quit();
+        }
+    }
+
+    /// Polls the project file for changes from a background thread, for as long as the board is
+    /// running.
+    fn watch(&self, siv: &mut Cursive) {
+        siv.set_user_data(self.clone());
+        let sink = siv.cb_sink().clone();
+        thread::spawn(move || loop {
+            thread::sleep(WATCH_INTERVAL);
+            let check = sink.send(Box::new(|s: &mut Cursive| {
+                if let Some(state) = s.user_data::<State>().cloned() {
+                    state.check_disk(s);
+                }
+            }));
+            if check.is_err() { break }
+        });
+    }
+
+    /// Picks up changes made to the project file outside of the board. These are loaded straight
+    /// away if there is nothing unsaved, otherwise the user is asked what to do with them.
+    fn check_disk(&self, siv: &mut Cursive) {
+        // don't interrupt a dialog or form that is open
+        if siv.screen().len() > 1 { return }
+        let on_disk = match self.git_project.borrow().revision_on_disk() {
+            Ok(on_disk) => on_disk,
+            Err(_) => return,
+        };
+        if on_disk == self.git_project.borrow().revision() || on_disk == self.dismissed_revision.get() { return }
+        self.dismissed_revision.set(on_disk);
+        self.task_commits.borrow_mut().take();
+
+        if !self.unsaved_changes() {
+            self.reload_from_disk(siv);
+            return;
+        }
+        let dialog = Dialog::text("The project file has been changed on disk, and you have unsaved changes. What would you like to do?")
+            .title("Changed on disk")
+            .button("Reload", { let state = self.clone(); move |s| {
+                s.pop_layer();
+                state.reload_from_disk(s);
+            }})
+            .button("Merge", { let state = self.clone(); move |s| {
+                s.pop_layer();
+                state.merge_from_disk(s);
+            }})
+            .button("Keep my changes", |s| { s.pop_layer(); });
+        siv.add_layer(dialog);
     }
 
     fn reload(&self, siv: &mut Cursive) {
         siv.pop_layer();
         let project_view = board::view(self.clone());
-        let global_events = OnEventView::new(project_view)
-            .on_event(event::Key::Esc, |s| s.select_menubar())
-            .on_event('?', help::show);
+        let keymap = &self.keymap;
+        let global_events = OnEventView::new(project_view);
+        let global_events = keymap.bind(global_events, Action::Menu, |s| s.select_menubar());
+        let global_events = keymap.bind(global_events, Action::Help, { let state = self.clone(); move |s| { help::show(&state.keymap, s) }});
+        let global_events = keymap.bind(global_events, Action::Undo, { let state = self.clone(); move |s| { state.undo(s) }});
+        let global_events = keymap.bind(global_events, Action::Redo, { let state = self.clone(); move |s| { state.redo(s) }});
+        let global_events = keymap.bind(global_events, Action::Filter, { let state = self.clone(); move |s| { s.add_layer(dialog::filter::text(state.clone())) }});
+        let global_events = keymap.bind(global_events, Action::FilterTag, { let state = self.clone(); move |s| { s.add_layer(dialog::filter::tag(state.clone())) }});
+        let global_events = keymap.bind(global_events, Action::FilterAssignee, { let state = self.clone(); move |s| { s.add_layer(dialog::filter::assignee(state.clone())) }});
+        let global_events = keymap.bind(global_events, Action::MyTasks, { let state = self.clone(); move |s| {
+            let mine = state.filter.borrow().mine;
+            state.filter.borrow_mut().mine = !mine;
+            state.refresh_board(s);
+        }});
         siv.add_fullscreen_layer(global_events);
+        self.update_edit_menu(siv);
+    }
+
+    /// Makes a change to the project that can be undone, if `change` returns true to say that it
+    /// changed something.
+    fn change<D, F>(&self, description: D, change: F) -> bool
+    where D: Into<String>, F: FnOnce(&mut GitProject) -> bool {
+        let before = self.git_project.borrow().clone();
+        let changed = change(&mut self.git_project.borrow_mut());
+        if changed {
+            self.record(description, before);
+        }
+        changed
     }
 
-    fn save(&self, siv: &mut Cursive) {
-        let result = self.git_project.borrow().save();
+    /// Records a change that has been made to the project, given the project as it was before.
+    fn record<D: Into<String>>(&self, description: D, before: GitProject) {
+        self.history.borrow_mut().record(description.into(), before);
+    }
+
+    /// Whether the project differs from the one last loaded or saved, which it does not once the
+    /// changes since are undone.
+    fn unsaved_changes(&self) -> bool {
+        self.history.borrow().position() != self.saved_position.get()
+    }
+
+    fn undo(&self, siv: &mut Cursive) {
+        let current = self.git_project.borrow().clone();
+        let previous = self.history.borrow_mut().undo(current);
+        if let Some(previous) = previous {
+            let mut git_project = self.git_project.borrow().clone();
+            git_project.restore(previous);
+            self.replace_git_project(git_project, siv);
+        }
+    }
+
+    fn redo(&self, siv: &mut Cursive) {
+        let current = self.git_project.borrow().clone();
+        let next = self.history.borrow_mut().redo(current);
+        if let Some(next) = next {
+            let mut git_project = self.git_project.borrow().clone();
+            git_project.restore(next);
+            self.replace_git_project(git_project, siv);
+        }
+    }
+
+    /// Shows what can be undone and redone in the Edit menu.
+    fn update_edit_menu(&self, siv: &mut Cursive) {
+        let history = self.history.borrow();
+        let undo = history.undo_description().map(|description| format!("Undo {}", description));
+        let redo = history.redo_description().map(|description| format!("Redo {}", description));
+        if let Some(edit_menu) = siv.menubar().find_subtree("Edit") {
+            edit_menu.clear();
+            edit_menu.add_leaf(undo.unwrap_or_else(|| "Nothing to undo".to_string()), { let state = self.clone(); move |s| { state.undo(s) }});
+            edit_menu.add_leaf(redo.unwrap_or_else(|| "Nothing to redo".to_string()), { let state = self.clone(); move |s| { state.redo(s) }});
+        }
+    }
+
+    /// Redraws the cards on the board after the filter changes, without rebuilding the rest of
+    /// the screen, so that any open filter dialog stays open.
+    fn refresh_board(&self, siv: &mut Cursive) {
+        let columns = board::columns(self.clone());
+        siv.call_on_name("board-columns", move |view: &mut LinearLayout| { *view = columns; });
+        let description = self.filter.borrow().describe();
+        siv.call_on_name("board-filter", move |view: &mut TextView| { view.set_content(description); });
+    }
+
+    fn save<F>(&self, siv: &mut Cursive, then: F)
+    where F: 'static + Fn(&mut Cursive) {
+        match self.git_project.borrow().changed_on_disk() {
+            Ok(false) => (),
+            Ok(true) => {
+                self.resolve_conflict(siv, then);
+                return;
+            }
+            Err(error) => {
+                self.handle_result::<(), _>(siv, Err(error));
+                return;
+            }
+        }
+        self.write(siv, then);
+    }
+
+    fn write<F>(&self, siv: &mut Cursive, then: F)
+    where F: 'static + Fn(&mut Cursive) {
+        let result = self.git_project.borrow_mut().save();
+        if result.is_ok() {
+            *self.saved_project.borrow_mut() = self.git_project.borrow().clone();
+            self.saved_position.set(self.history.borrow().position());
+            then(siv);
+        }
         self.handle_result(siv, result);
     }
 
+    fn replace_git_project(&self, git_project: GitProject, siv: &mut Cursive) {
+        let last_project = git_project.projects().len().saturating_sub(1);
+        if self.selected_project.get() > last_project {
+            self.selected_project.set(last_project);
+        }
+        *self.git_project.borrow_mut() = git_project;
+        self.reload(siv);
+    }
+
+    fn resolve_conflict<F>(&self, siv: &mut Cursive, then: F)
+    where F: 'static + Fn(&mut Cursive) {
+        let then = Rc::new(then);
+        let dialog = Dialog::text("The project file has been changed on disk since it was opened. What would you like to do?")
+            .title("Conflict")
+            .button("Cancel", |s| { s.pop_layer(); })
+            .button("Reload", { let state = self.clone(); move |s| {
+                s.pop_layer();
+                state.reload_from_disk(s);
+            }})
+            .button("Merge", { let state = self.clone(); let then = then.clone(); move |s| {
+                s.pop_layer();
+                if state.merge_from_disk(s) {
+                    let then = then.clone();
+                    state.write(s, move |s| then(s));
+                }
+            }})
+            .button("Overwrite", { let state = self.clone(); move |s| {
+                s.pop_layer();
+                let then = then.clone();
+                state.write(s, move |s| then(s));
+            }});
+        siv.add_layer(dialog);
+    }
+
+    /// Replaces the project with the one on disk, dropping any unsaved changes.
+    fn reload_from_disk(&self, siv: &mut Cursive) {
+        match GitProject::open() {
+            Ok(theirs) => {
+                self.record("reload from disk", self.git_project.borrow().clone());
+                *self.saved_project.borrow_mut() = theirs.clone();
+                self.saved_position.set(self.history.borrow().position());
+                self.replace_git_project(theirs, siv);
+            }
+            Err(error) => self.handle_result::<(), _>(siv, Err(error)),
+        }
+    }
+
+    /// Merges the changes made on disk into the unsaved changes, returning whether it succeeded.
+    /// The merged project still has to be saved.
+    fn merge_from_disk(&self, siv: &mut Cursive) -> bool {
+        match GitProject::open() {
+            Ok(theirs) => {
+                let merged = self.git_project.borrow().merge(&self.saved_project.borrow(), &theirs);
+                self.record("merge with disk", self.git_project.borrow().clone());
+                *self.saved_project.borrow_mut() = theirs;
+                self.replace_git_project(merged, siv);
+                true
+            }
+            Err(error) => {
+                self.handle_result::<(), _>(siv, Err(error));
+                false
+            }
+        }
+    }
+
     fn quit(&self, siv: &mut Cursive) {
-        if self.unsaved_changes.get() {
+        if self.unsaved_changes() {
             let dialog = Dialog::text("Save before quitting?")
                 .button("Cancel", |s| { s.pop_layer(); })
                 .button("Quit without saving", Cursive::quit)
-                .button("Save and quit", { let state = self.clone(); move |s| { state.save(s); s.quit(); }});
+                .button("Save and quit", { let state = self.clone(); move |s| { state.save(s, Cursive::quit); }});
             siv.add_layer(dialog);
         } else {
             siv.quit();
@@ -91,6 +402,16 @@ impl State {
         }
     }
 
+    /// The commits that changed each task, or none if they cannot be read.
+    fn task_commits(&self) -> Rc<TaskCommits> {
+        self.task_commits.borrow_mut()
+            .get_or_insert_with(|| {
+                let task_commits = Repository::open_from_env().ok().and_then(|repository| TaskCommits::load(&repository).ok());
+                Rc::new(task_commits.unwrap_or_default())
+            })
+            .clone()
+    }
+
     fn show_task(&self, task: Task, siv: &mut Cursive) {
         let task_dialog = dialog::task::task(self.clone(), task);
         siv.add_layer(task_dialog);
@@ -112,19 +433,33 @@ impl State {
     }
 
     fn move_task_right(&self, task: &Task, siv: &mut Cursive) {
-        let mut git_project = self.git_project.borrow_mut();
-        let project = &mut git_project.projects_mut()[self.selected_project.get()];
-        project.move_task(task, 1);
-        std::mem::drop(git_project);
-        self.reload(siv);
+        self.change_task_position(task, siv, |project, task| project.move_task(task, 1));
     }
 
     fn move_task_left(&self, task: &Task, siv: &mut Cursive) {
-        let mut git_project = self.git_project.borrow_mut();
-        let project = &mut git_project.projects_mut()[self.selected_project.get()];
-        project.move_task(task, -1);
-        std::mem::drop(git_project);
+        self.change_task_position(task, siv, |project, task| project.move_task(task, -1));
+    }
+
+    fn move_task_up(&self, task: &Task, siv: &mut Cursive) {
+        self.change_task_position(task, siv, |project, task| project.reorder_task(task, -1));
+    }
+
+    fn move_task_down(&self, task: &Task, siv: &mut Cursive) {
+        self.change_task_position(task, siv, |project, task| project.reorder_task(task, 1));
+    }
+
+    /// Moves a task on the board, keeping the moved task focused once the board is redrawn.
+    fn change_task_position<F>(&self, task: &Task, siv: &mut Cursive, change: F)
+    where F: FnOnce(&mut Project, &Task) {
+        let selected_project = self.selected_project.get();
+        self.change(format!("move task {}", task.id()), |git_project| {
+            let project = &mut git_project.projects_mut()[selected_project];
+            let before = project.position_of_task(task);
+            change(project, task);
+            project.position_of_task(task) != before
+        });
         self.reload(siv);
+        siv.focus_name(task.id().as_ref()).ok();
     }
 
     fn edit_project(&self, project: Project, siv: &mut Cursive) {
@@ -0,0 +1,40 @@
+use std::error::Error;
+use std::fs::read_to_string;
+use std::io::{stdin, Read};
+use std::path::PathBuf;
+use git2::Repository;
+use structopt::StructOpt;
+use crate::model::*;
+use super::apply_commands;
+
+#[derive(Debug, StructOpt)]
+pub struct ParseMessage {
+    /// The file containing the commit message. The message is read from standard input if this
+    /// is omitted or `-`.
+    file: Option<PathBuf>,
+}
+
+pub fn parse_message(args: ParseMessage) -> Result<(), Box<dyn Error>> {
+    let repository = Repository::open_from_env()?;
+    let message = match args.file {
+        Some(ref file) if file.as_os_str() != "-" => read_to_string(file)?,
+        _ => {
+            let mut message = String::new();
+            stdin().read_to_string(&mut message)?;
+            message
+        }
+    };
+
+    // the project is never saved, so the commands only change this copy of it
+    let mut git_project = GitProject::open()?;
+    let transitions = apply_commands(&repository, &mut git_project, &message)?;
+    if transitions.is_empty() {
+        println!("This message would not change the board");
+        return Ok(());
+    }
+    println!("This message would change the board:");
+    for transition in transitions {
+        println!("\t{}", transition);
+    }
+    Ok(())
+}
@@ -5,7 +5,8 @@ mod model;
 use commands::*;
 
 const PROJECT_FILE_NAME: &'static str = ".gitproject";
-const PROJECT_TEMP_FILE: &'static str = ".git/.gitproject~";
+const PROJECT_STATE_FILE_NAME: &str = "git-project-state";
+const NOTES_REF: &str = "refs/notes/git-project";
 
 /// Git-based project boards.
 #[derive(Debug, StructOpt)]
@@ -16,6 +17,15 @@ enum Args {
     Init(Init),
     /// Check the tasks currently assigned to you
     Current,
+    /// Converts the project file to a different format
+    Convert(Convert),
+    /// Inspect the board changes recorded on commits
+    Notes(Notes),
+    /// Shows how a commit message would change the board, without changing it.
+    ///
+    /// The commands in the message are checked exactly as they would be when committing, so this
+    /// can be used to check commit messages from editors or CI. Fails if any command is invalid.
+    ParseMessage(ParseMessage),
     /// Adds the hooks to your repository. 
     ///
     /// The git-project hooks will be appended to the end of your existing Git hooks, if any.
@@ -27,7 +37,12 @@ enum Args {
     ///
     /// Note that hooks are *not* included when distributing your repository, so subsequent clones
     /// will require you to install hooks again.
-    Hooks,
+    ///
+    /// If there is an `origin` remote, it is also set up to fetch the board notes, so that the
+    /// commits your teammates made for a task are shown with it.
+    ///
+    /// Use `--uninstall` to remove the hooks again, or `--status` to check which are installed.
+    Hooks(Hooks),
     /// The built in Git hooks. Not meant to be used manually.
     Hook(Hook),
 }
@@ -38,7 +53,10 @@ fn main(args: Args) {
         Args::Open => open(),
         Args::Init(args) => init(args),
         Args::Current => current(),
-        Args::Hooks => hooks(),
+        Args::Convert(args) => convert(args),
+        Args::Notes(args) => notes(args),
+        Args::ParseMessage(args) => parse_message(args),
+        Args::Hooks(args) => hooks(args),
         Args::Hook(args) => hook(args),
     };
 
@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 use super::{Task, Id};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Column {
     id: Id,
     name: String,
@@ -34,6 +34,16 @@ impl Column {
         self.tasks.push(task);
     }
 
+    /// Inserts a task at a position in this column, or at the end if the position is past it.
+    pub fn insert_task_id(&mut self, position: usize, task: Id) {
+        let position = position.min(self.tasks.len());
+        self.tasks.insert(position, task);
+    }
+
+    pub fn position_of_task(&self, task: &Id) -> Option<usize> {
+        self.tasks.iter().position(|id| id == task)
+    }
+
     pub fn add_task(&mut self, task: &Task) {
         self.tasks.push(task.id().clone());
     }
@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use git2::Repository;
+use serde::{Serialize, de::DeserializeOwned};
+use crate::PROJECT_FILE_NAME;
+
+const FORMAT_CONFIG_KEY: &str = "project.format";
+
+#[derive(Debug)]
+pub struct FormatError(String);
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown format {}. Supported formats are toml, json and yaml.", self.0)
+    }
+}
+impl Error for FormatError {}
+
+/// The file formats a project board can be stored in.
+#[derive(Eq, PartialEq, Copy, Clone, Default, Debug)]
+pub enum Format {
+    #[default]
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Toml, Format::Json, Format::Yaml];
+
+    /// Determines the format of the project file in a repository, using the `project.format`
+    /// config key if set, or otherwise whichever project file exists.
+    pub fn detect<F>(repository: &Repository, exists: F) -> Result<Format, Box<dyn Error>>
+    where F: Fn(&str) -> bool {
+        let config = repository.config()?.snapshot()?;
+        if let Ok(format) = config.get_str(FORMAT_CONFIG_KEY) {
+            return Ok(format.parse()?);
+        }
+        Ok(Self::ALL.iter()
+            .cloned()
+            .find(|format| exists(&format.file_name()))
+            .unwrap_or_default())
+    }
+
+    /// Records this format in the repository config if a format was configured at any level, as
+    /// that would otherwise still be used in place of the new one.
+    pub fn update_config(self, repository: &Repository) -> Result<(), Box<dyn Error>> {
+        let mut config = repository.config()?;
+        if config.snapshot()?.get_str(FORMAT_CONFIG_KEY).is_ok() {
+            let mut local = config.open_level(git2::ConfigLevel::Local)?;
+            local.set_str(FORMAT_CONFIG_KEY, self.name())?;
+        }
+        Ok(())
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Toml => "toml",
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+        }
+    }
+
+    pub fn file_name(self) -> String {
+        match self {
+            Format::Toml => PROJECT_FILE_NAME.to_string(),
+            _ => format!("{}.{}", PROJECT_FILE_NAME, self.name()),
+        }
+    }
+
+    pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, Box<dyn Error>> {
+        Ok(match self {
+            Format::Toml => toml::to_string_pretty(value)?,
+            Format::Json => serde_json::to_string_pretty(value)? + "\n",
+            Format::Yaml => serde_yaml::to_string(value)? + "\n",
+        })
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(self, string: &str) -> Result<T, Box<dyn Error>> {
+        Ok(match self {
+            Format::Toml => toml::from_str(string)?,
+            Format::Json => serde_json::from_str(string)?,
+            Format::Yaml => serde_yaml::from_str(string)?,
+        })
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = FormatError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string.to_ascii_lowercase().as_str() {
+            "toml" => Ok(Format::Toml),
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            _ => Err(FormatError(string.to_string())),
+        }
+    }
+}
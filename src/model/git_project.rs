@@ -1,11 +1,14 @@
-use std::env::current_dir;
+use std::collections::hash_map::DefaultHasher;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
-use std::fs::{read_to_string, File};
+use std::fs::{metadata, read_to_string, File};
+use std::hash::{Hash, Hasher};
 use std::io::Write;
-use git2::Repository;
+use std::path::{Path, PathBuf};
+use git2::{Repository, Tree};
+use tempfile::NamedTempFile;
 use serde::{Serialize, Deserialize};
-use super::Project;
+use super::{Project, Id, Format, Theme};
 use crate::PROJECT_FILE_NAME;
 
 #[derive(Debug)]
@@ -17,9 +20,42 @@ impl Display for OpenError {
 }
 impl Error for OpenError {}
 
+#[derive(Debug)]
+struct BareRepositoryError;
+impl Display for BareRepositoryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "This is a bare repository, so the project can only be read from HEAD. Run this command in a repository with a working tree instead.")
+    }
+}
+impl Error for BareRepositoryError {}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct GitProject {
     projects: Vec<Project>,
+    #[serde(default, skip_serializing_if = "Theme::is_empty")]
+    theme: Theme,
+    /// Hash of the file contents this was last read from or written to, if any.
+    #[serde(skip)]
+    revision: Option<u64>,
+    #[serde(skip)]
+    format: Format,
+}
+
+#[cfg(unix)]
+fn set_default_permissions(file: &File) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    file.set_permissions(std::fs::Permissions::from_mode(0o644))
+}
+
+#[cfg(not(unix))]
+fn set_default_permissions(_: &File) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn content_hash(contents: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
 }
 
 impl GitProject {
@@ -27,28 +63,139 @@ impl GitProject {
         GitProjectBuilder::new()
     }
 
+    fn workdir() -> Result<(Repository, PathBuf), Box<dyn Error>> {
+        let repository = Repository::open_from_env()?;
+        let workdir = match repository.workdir() {
+            Some(workdir) => workdir.to_path_buf(),
+            None => return Err(Box::new(BareRepositoryError)),
+        };
+        Ok((repository, workdir))
+    }
+
+    /// Finds the project file of the current repository, in whichever format it is stored.
+    pub fn locate() -> Result<(PathBuf, Format), Box<dyn Error>> {
+        let (repository, workdir) = Self::workdir()?;
+        let format = Format::detect(&repository, |file_name| workdir.join(file_name).exists())?;
+        Ok((workdir.join(format.file_name()), format))
+    }
+
     pub fn open() -> Result<GitProject, Box<dyn Error>> {
-        let repository = Repository::discover(current_dir()?)?;
-        let workdir = repository.workdir().unwrap();
-        let root = workdir.join(PROJECT_FILE_NAME);
+        let repository = Repository::open_from_env()?;
+        if repository.is_bare() {
+            return Self::open_from_head(&repository);
+        }
+        let (root, format) = Self::locate()?;
         if !root.exists() { return Err(Box::new(OpenError)) }
         let string = read_to_string(root)?;
-        let git_project = toml::from_str(&string)?;
+        let mut git_project: GitProject = format.deserialize(&string)?;
+        git_project.revision = Some(content_hash(&string));
+        git_project.format = format;
 
         Ok(git_project)
     }
 
-    pub fn save(&self) -> Result<(), Box<dyn Error>> {
-        let repository = Repository::discover(current_dir()?)?;
-        let workdir = repository.workdir().unwrap();
-        let root = workdir.join(PROJECT_FILE_NAME);
+    /// Reads the project as it was committed at HEAD, for repositories without a working tree.
+    fn open_from_head(repository: &Repository) -> Result<GitProject, Box<dyn Error>> {
+        let tree = repository.head()?.peel_to_tree()?;
+        match Self::open_from_tree(repository, &tree)? {
+            Some(git_project) => Ok(git_project),
+            None => Err(Box::new(OpenError)),
+        }
+    }
+
+    /// Reads the project as it was committed in a tree, if there is a project file there.
+    pub fn open_from_tree(repository: &Repository, tree: &Tree) -> Result<Option<GitProject>, Box<dyn Error>> {
+        let format = Format::detect(repository, |file_name| tree.get_name(file_name).is_some())?;
+        let entry = match tree.get_name(&format.file_name()) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let blob = entry.to_object(repository)?.peel_to_blob()?;
+        let mut git_project: GitProject = format.deserialize(std::str::from_utf8(blob.content())?)?;
+        git_project.format = format;
+
+        Ok(Some(git_project))
+    }
+
+    /// The path this project is saved to, based on its format.
+    pub fn path(&self) -> Result<PathBuf, Box<dyn Error>> {
+        Ok(Self::workdir()?.1.join(self.format.file_name()))
+    }
 
-        let project_string = toml::to_string_pretty(self)?;
-        let mut file = File::create(root)?;
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    /// Writes the project file atomically, by writing to a temporary file first and then
+    /// renaming it over the original. Each save has a temporary file of its own, so that the board
+    /// and a hook saving at the same time cannot write into each other's.
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        let root = self.path()?;
+        let mut file = NamedTempFile::new_in(root.parent().unwrap_or_else(|| Path::new(".")))?;
+
+        let project_string = self.format.serialize(self)?;
         write!(file, "{}", project_string)?;
+        file.as_file().sync_all()?;
+        // the temporary file is only readable by its owner, which the project file should not be
+        match metadata(&root) {
+            Ok(existing) => file.as_file().set_permissions(existing.permissions())?,
+            Err(..) => set_default_permissions(file.as_file())?,
+        }
+        file.persist(&root)?;
+
+        self.revision = Some(content_hash(&project_string));
         Ok(())
     }
 
+    /// Hash of the file contents this was last read from or written to, if any.
+    pub fn revision(&self) -> Option<u64> {
+        self.revision
+    }
+
+    /// Hash of the project file as it is on disk now, if it exists.
+    pub fn revision_on_disk(&self) -> Result<Option<u64>, Box<dyn Error>> {
+        let root = self.path()?;
+        Ok(if root.exists() { Some(content_hash(&read_to_string(root)?)) } else { None })
+    }
+
+    /// Whether the project file has been changed by someone else since this was opened or
+    /// last saved.
+    pub fn changed_on_disk(&self) -> Result<bool, Box<dyn Error>> {
+        Ok(self.revision_on_disk()? != self.revision)
+    }
+
+    /// Takes the projects from another version of this project, such as an earlier snapshot,
+    /// while still tracking the file this one was last read from or written to.
+    pub fn restore(&mut self, other: GitProject) {
+        self.projects = other.projects;
+    }
+
+    /// Performs a three way merge of the changes made in `self` and in `theirs`, since they
+    /// diverged from `base`. Where both sides changed the same thing, `self` wins.
+    pub fn merge(&self, base: &GitProject, theirs: &GitProject) -> GitProject {
+        let mut ids: Vec<&Id> = self.projects.iter().map(Project::id).collect();
+        for project in &theirs.projects {
+            if !ids.contains(&project.id()) { ids.push(project.id()); }
+        }
+        let find = |git_project: &'_ GitProject, id: &Id| git_project.projects.iter().find(|project| project.id() == id).cloned();
+        let projects = ids.into_iter()
+            .filter_map(|id| match (find(base, id), find(self, id), find(theirs, id)) {
+                (base, Some(ours), Some(theirs)) => Some(ours.merge(base.as_ref(), &theirs)),
+                (base, ours, theirs) => if ours == base { theirs } else { ours },
+            })
+            .collect();
+        GitProject {
+            projects,
+            theme: if self.theme == base.theme { theirs.theme.clone() } else { self.theme.clone() },
+            revision: theirs.revision,
+            format: self.format,
+        }
+    }
+
     pub fn projects(&self) -> &[Project] {
         self.projects.as_slice()
     }
@@ -57,6 +204,26 @@ impl GitProject {
         self.projects.as_mut()
     }
 
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Generates the ID of a new project from its name, which is given a number if another project
+    /// already has it, or is replaced by one when the name has nothing to make an ID of.
+    pub fn generate_project_id(&self, name: &str) -> Id {
+        let mut base = Id::slug(name);
+        if !base.is_valid() {
+            base = Id::new("project");
+        }
+        let mut id = base.clone();
+        let mut suffix = 1;
+        while self.projects.iter().any(|project| project.id() == &id) {
+            suffix += 1;
+            id = Id::new(format!("{}-{}", base, suffix));
+        }
+        id
+    }
+
     pub fn add_project(&mut self, project: Project) -> bool {
         if self.projects.iter().find(|original| original.name() == project.name()).is_some() { return false; }
         self.projects.push(project);
@@ -93,7 +260,52 @@ impl GitProjectBuilder {
 
     pub fn build(self) -> Result<GitProject, Self> {
         Ok(GitProject { 
-            projects: self.projects
+            projects: self.projects,
+            theme: Theme::default(),
+            revision: None,
+            format: Format::default(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(id: &str, name: &str) -> Project {
+        Project::new(id).name(name).description("").build().unwrap()
+    }
+
+    fn names(git_project: &GitProject) -> Vec<&str> {
+        git_project.projects().iter().map(Project::name).collect()
+    }
+
+    #[test]
+    fn merges_projects_added_and_deleted_on_either_side() {
+        let base = GitProject::new().project(project("web", "Web")).project(project("api", "API")).build().unwrap();
+        let mut ours = base.clone();
+        ours.add_project(project("docs", "Docs"));
+        let mut theirs = base.clone();
+        theirs.delete_project(1);
+
+        assert_eq!(names(&ours.merge(&base, &theirs)), vec!["Web", "Docs"]);
+    }
+
+    #[test]
+    fn merges_changes_within_a_project() {
+        let base = GitProject::new().project(project("web", "Web")).build().unwrap();
+        let ours = base.clone();
+        let mut theirs = base.clone();
+        theirs.replace_project("Web", project("web", "Website"));
+
+        assert_eq!(names(&ours.merge(&base, &theirs)), vec!["Website"]);
+    }
+
+    #[test]
+    fn generates_unique_project_ids() {
+        let git_project = GitProject::new().project(project("web", "Web")).project(project("web-2", "Web")).build().unwrap();
+        assert_eq!(git_project.generate_project_id("API"), Id::new("api"));
+        assert_eq!(git_project.generate_project_id("Web!"), Id::new("web-3"));
+        assert_eq!(git_project.generate_project_id("?!"), Id::new("project"));
+    }
+}
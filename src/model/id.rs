@@ -6,6 +6,24 @@ pub struct Id(String);
 
 impl Id {
     pub fn new<I: AsRef<str>>(id: I) -> Self { Self(id.as_ref().to_string()) }
+
+    /// Converts some text into an ID made of its first few words, lowercased and joined by dashes.
+    pub fn slug<I: AsRef<str>>(text: I) -> Self {
+        let words: Vec<String> = text.as_ref()
+            .split(|ch: char| !ch.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .take(6)
+            .map(str::to_lowercase)
+            .collect();
+        Self(words.join("-"))
+    }
+
+    /// Whether this ID can be referenced in commit message commands, which requires it be
+    /// non-empty and not contain any whitespace, square brackets, or the @ that separates a task
+    /// from its project.
+    pub fn is_valid(&self) -> bool {
+        !self.0.is_empty() && !self.0.contains(|ch: char| ch.is_whitespace() || ch == '[' || ch == ']' || ch == '@')
+    }
 }
 
 impl Display for Id {
@@ -33,3 +51,30 @@ impl Into<String> for &Id {
 impl AsRef<str> for Id {
     fn as_ref(&self) -> &str { &self.0 }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_take_the_first_words() {
+        assert_eq!(Id::slug("Fix the Login form"), Id::new("fix-the-login-form"));
+        assert_eq!(Id::slug("  [WIP] don't crash, please! "), Id::new("wip-don-t-crash-please"));
+        assert_eq!(Id::slug("one two three four five six seven"), Id::new("one-two-three-four-five-six"));
+        assert_eq!(Id::slug("Café ünicode"), Id::new("café-ünicode"));
+    }
+
+    #[test]
+    fn slugs_of_punctuation_are_empty() {
+        assert_eq!(Id::slug("?!"), Id::new(""));
+        assert!(!Id::slug("?!").is_valid());
+    }
+
+    #[test]
+    fn ids_cannot_contain_what_commands_use() {
+        assert!(Id::new("fix-login").is_valid());
+        assert!(!Id::new("fix login").is_valid());
+        assert!(!Id::new("[fix]").is_valid());
+        assert!(!Id::new("fix@web").is_valid());
+    }
+}
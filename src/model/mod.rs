@@ -1,11 +1,17 @@
+mod format;
 mod git_project;
 mod id;
 mod project;
 mod column;
 mod task;
+mod transition;
+mod theme;
 
 pub use id::*;
+pub use format::*;
 pub use git_project::*;
 pub use project::*;
 pub use column::*;
 pub use task::*;
+pub use transition::*;
+pub use theme::*;
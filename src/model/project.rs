@@ -2,15 +2,23 @@ use std::collections::BTreeSet;
 use serde::{Serialize, Deserialize};
 use super::{Column, Task, Id};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Project {
     id: Id,
     name: String,
     description: String,
+    /// The pattern new task IDs are generated from. See `Project::generate_task_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task_id_pattern: Option<String>,
+    /// The last number used for the `{n}` placeholder of the task ID pattern.
+    #[serde(default)]
+    task_counter: usize,
     columns: Vec<Column>,
     tasks: Vec<Task>,
 }
 
+const DEFAULT_TASK_ID_PATTERN: &str = "{project}-{n}";
+
 impl Project {
     pub fn new<I: AsRef<str>>(id: I) -> ProjectBuilder {
         ProjectBuilder::new(id.as_ref().to_string().into())
@@ -28,6 +36,14 @@ impl Project {
         self.description.as_str()
     }
 
+    pub fn task_id_pattern(&self) -> &str {
+        self.task_id_pattern.as_deref().unwrap_or(DEFAULT_TASK_ID_PATTERN)
+    }
+
+    pub fn task_counter(&self) -> usize {
+        self.task_counter
+    }
+
     pub fn columns(&self) -> &[Column] {
         self.columns.as_slice()
     }
@@ -64,6 +80,39 @@ impl Project {
             .collect()
     }
 
+    /// Generates a unique ID for a new task from the task ID pattern. The pattern may include
+    /// the placeholders `{project}` for the project ID, `{n}` for an increasing number, and
+    /// `{slug}` for a shortened version of the task's title.
+    pub fn generate_task_id(&mut self, title: &str) -> Id {
+        let pattern = self.task_id_pattern().to_string();
+        let slug = Id::slug(title);
+        loop {
+            let uses_counter = pattern.contains("{n}");
+            if uses_counter { self.task_counter += 1; }
+            let generated = pattern
+                .replace("{project}", self.id.as_ref())
+                .replace("{n}", &self.task_counter.to_string())
+                .replace("{slug}", slug.as_ref())
+                .replace(|ch: char| ch.is_whitespace() || ch == '[' || ch == ']' || ch == '@', "-");
+            let mut id = Id::new(generated.trim_matches('-'));
+            if !id.is_valid() {
+                self.task_counter += 1;
+                id = Id::new(format!("{}-{}", self.id, self.task_counter));
+            }
+            if !uses_counter {
+                let mut suffix = 1;
+                let base = id.clone();
+                while self.task_with_id(&id).is_some() {
+                    suffix += 1;
+                    id = Id::new(format!("{}-{}", base, suffix));
+                }
+            }
+            if self.task_with_id(&id).is_none() {
+                return id;
+            }
+        }
+    }
+
     pub fn add_task(&mut self, task: Task, column: usize) -> bool {
         if self.tasks.iter().find(|t| t.id() == task.id()).is_some() { return false }
         self.columns[column].add_task(&task);
@@ -73,10 +122,12 @@ impl Project {
 
     pub fn replace_task(&mut self, original_task: &Id, task: Task, column: Option<usize>) {
         if let Some(column_index) = column {
+            // a task that stays in the same column keeps its place there
+            let position = self.columns[column_index].position_of_task(original_task).unwrap_or(usize::MAX);
             for column in self.columns.iter_mut() {
                 column.remove_task(original_task);
             }
-            self.columns[column_index].add_task(&task);
+            self.columns[column_index].insert_task_id(position, task.id().clone());
         }
         self.tasks.retain(|task| task.id() != original_task);
         self.tasks.push(task);
@@ -89,16 +140,43 @@ impl Project {
         self.tasks.retain(|task| task.id() != task_id);
     }
 
+    /// The column a task is in, and its position within that column.
+    pub fn position_of_task(&self, task: &Task) -> Option<(usize, usize)> {
+        self.columns.iter()
+            .enumerate()
+            .find_map(|(column, col)| col.position_of_task(task.id()).map(|position| (column, position)))
+    }
+
+    /// Moves a task to a position in a column, or to the end of the column if the position is
+    /// past it.
+    pub fn move_task_to(&mut self, task: &Task, column: usize, position: usize) {
+        if column >= self.columns.len() { return; }
+        for column in self.columns.iter_mut() {
+            column.remove_task(task.id());
+        }
+        self.columns[column].insert_task_id(position, task.id().clone());
+    }
+
+    /// Moves a task across columns by `distance`, keeping its position within the column where
+    /// possible.
     pub fn move_task(&mut self, task: &Task, distance: isize) {
-        let previous_column = match self.column_index_of_task(task) {
-            Some(column) => column,
+        let (previous_column, position) = match self.position_of_task(task) {
+            Some(position) => position,
             None => return,
         };
         let new_column = previous_column as isize + distance;
-        if new_column < 0 || new_column > self.columns.len() as isize { return; }
-        let new_column = new_column as usize;
-        self.columns[previous_column].remove_task(task.id());
-        self.columns[new_column].add_task(task);
+        if new_column < 0 || new_column >= self.columns.len() as isize { return; }
+        self.move_task_to(task, new_column as usize, position);
+    }
+
+    /// Moves a task up (for a negative `distance`) or down within its column.
+    pub fn reorder_task(&mut self, task: &Task, distance: isize) {
+        let (column, position) = match self.position_of_task(task) {
+            Some(position) => position,
+            None => return,
+        };
+        let new_position = (position as isize + distance).max(0) as usize;
+        self.move_task_to(task, column, new_position);
     }
 
     pub fn move_task_to_column(&mut self, task_id: Id, column_id: Id) {
@@ -112,6 +190,70 @@ impl Project {
             column.add_task_id(task_id);
         }
     }
+
+    /// Performs a three way merge of the changes made in `self` and in `theirs`, since they
+    /// diverged from `base`. Where both sides changed the same thing, `self` wins.
+    pub fn merge(&self, base: Option<&Project>, theirs: &Project) -> Project {
+        fn pick<T: PartialEq>(base: Option<T>, ours: Option<T>, theirs: Option<T>) -> Option<T> {
+            if ours == base { theirs } else { ours }
+        }
+        fn placement(project: &Project, task_id: &Id) -> Option<Id> {
+            project.columns.iter()
+                .find(|column| column.tasks().contains(task_id))
+                .map(|column| column.id().clone())
+        }
+        fn empty_columns(project: &Project) -> Vec<Column> {
+            project.columns.iter().map(Column::without_tasks).collect()
+        }
+
+        let name = pick(base.map(Project::name), Some(self.name()), Some(theirs.name())).unwrap();
+        let description = pick(base.map(Project::description), Some(self.description()), Some(theirs.description())).unwrap();
+        let task_id_pattern = pick(base.map(|base| &base.task_id_pattern), Some(&self.task_id_pattern), Some(&theirs.task_id_pattern)).unwrap();
+
+        let mut task_ids: Vec<&Id> = self.tasks.iter().map(Task::id).collect();
+        for task in &theirs.tasks {
+            if !task_ids.contains(&task.id()) { task_ids.push(task.id()); }
+        }
+        let tasks: Vec<Task> = task_ids.into_iter()
+            .filter_map(|id| {
+                // moving a task is a change to it too, so a task we moved is kept if they deleted it
+                let moved = base.map(|base| placement(base, id)) != Some(placement(self, id));
+                match self.task_with_id(id) {
+                    Some(ours) if moved => Some(ours),
+                    ours => pick(base.and_then(|base| base.task_with_id(id)), ours, theirs.task_with_id(id)),
+                }
+            })
+            .cloned()
+            .collect();
+        let placements: Vec<(Id, Option<Id>)> = tasks.iter()
+            .map(|task| (task.id().clone(), pick(base.map(|base| placement(base, task.id())), Some(placement(self, task.id())), Some(placement(theirs, task.id()))).unwrap()))
+            .collect();
+
+        let mut columns = pick(base.map(empty_columns), Some(empty_columns(self)), Some(empty_columns(theirs))).unwrap();
+        for column in columns.iter_mut() {
+            let column_id = column.id().clone();
+            let original_order = self.columns.iter()
+                .chain(theirs.columns.iter())
+                .filter(|original| original.id() == &column_id)
+                .flat_map(|original| original.tasks());
+            for task_id in original_order.chain(tasks.iter().map(Task::id)) {
+                let placed_here = placements.iter().any(|(id, placement)| id == task_id && placement.as_ref() == Some(&column_id));
+                if placed_here && !column.tasks().contains(task_id) {
+                    column.add_task_id(task_id.clone());
+                }
+            }
+        }
+
+        Project {
+            id: self.id.clone(),
+            name: name.to_string(),
+            description: description.to_string(),
+            task_id_pattern: task_id_pattern.clone(),
+            task_counter: self.task_counter.max(theirs.task_counter),
+            columns,
+            tasks,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -119,6 +261,8 @@ pub struct ProjectBuilder {
     id: Id,
     name: Option<String>,
     description: Option<String>,
+    task_id_pattern: Option<String>,
+    task_counter: usize,
     columns: Vec<Column>,
     tasks: Vec<Task>,
 }
@@ -129,6 +273,8 @@ impl ProjectBuilder {
             id,
             name: None,
             description: None,
+            task_id_pattern: None,
+            task_counter: 0,
             columns: vec![],
             tasks: vec![],
         }
@@ -148,6 +294,20 @@ impl ProjectBuilder {
         }
     }
 
+    pub fn task_id_pattern<I: AsRef<str>>(self, task_id_pattern: I) -> Self {
+        Self {
+            task_id_pattern: Some(task_id_pattern.as_ref().to_string()),
+            ..self
+        }
+    }
+
+    pub fn task_counter(self, task_counter: usize) -> Self {
+        Self {
+            task_counter,
+            ..self
+        }
+    }
+
     pub fn column(mut self, column: Column) -> Self {
         self.columns.push(column);
         self
@@ -158,10 +318,137 @@ impl ProjectBuilder {
         self
     }
 
-    pub fn build(self) -> Result<Project, Self> {
+    pub fn build(self) -> Result<Project, Box<Self>> {
         match self {
-            ProjectBuilder { id, name: Some(name), description: Some(description), columns, tasks } => Ok(Project { id, name, description, columns, tasks }),
-            _=> Err(self),
+            ProjectBuilder { id, name: Some(name), description: Some(description), task_id_pattern, task_counter, columns, tasks } => Ok(Project { id, name, description, task_id_pattern, task_counter, columns, tasks }),
+            _=> Err(Box::new(self)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(pattern: Option<&str>, task_ids: &[&str]) -> Project {
+        let builder = Project::new("web").name("Web").description("");
+        let builder = match pattern {
+            Some(pattern) => builder.task_id_pattern(pattern),
+            None => builder,
+        };
+        task_ids.iter()
+            .fold(builder, |builder, id| builder.task(Task::new(id).name(id).description("").build().unwrap()))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn generates_numbered_ids_by_default() {
+        let mut project = project(None, &[]);
+        assert_eq!(project.generate_task_id("Login form"), Id::new("web-1"));
+        assert_eq!(project.generate_task_id("Login form"), Id::new("web-2"));
+        assert_eq!(project.task_counter(), 2);
+    }
+
+    #[test]
+    fn skips_numbers_already_taken() {
+        let mut project = project(None, &["web-1", "web-2"]);
+        assert_eq!(project.generate_task_id("Login form"), Id::new("web-3"));
+    }
+
+    #[test]
+    fn generates_ids_from_the_title() {
+        let mut project = project(Some("{slug}"), &["login-form"]);
+        assert_eq!(project.generate_task_id("Fix typo"), Id::new("fix-typo"));
+        assert_eq!(project.generate_task_id("Login form"), Id::new("login-form-2"));
+        assert_eq!(project.task_counter(), 0);
+    }
+
+    #[test]
+    fn fills_in_every_placeholder() {
+        let mut project = project(Some("{project}/{n} {slug}"), &[]);
+        assert_eq!(project.generate_task_id("Fix [the] typo"), Id::new("web/1-fix-the-typo"));
+    }
+
+    #[test]
+    fn leaves_out_the_at_sign_of_a_pattern() {
+        let mut project = project(Some("{n}@{project}"), &[]);
+        assert_eq!(project.generate_task_id("Fix typo"), Id::new("1-web"));
+    }
+
+    #[test]
+    fn falls_back_to_a_number_when_the_pattern_gives_nothing() {
+        let mut project = project(Some("{slug}"), &[]);
+        assert_eq!(project.generate_task_id("?!"), Id::new("web-1"));
+    }
+
+    fn board(todo: &[&str], done: &[&str]) -> Project {
+        let task = |id: &&str| Task::new(id).name(id).description("").build().unwrap();
+        let column = |id: &str, tasks: &[&str]| tasks.iter()
+            .fold(Column::new(id).name(id).description(""), |column, id| column.add_task(&task(id)))
+            .build()
+            .unwrap();
+        todo.iter().chain(done)
+            .fold(Project::new("web").name("Web").description("").column(column("todo", todo)).column(column("done", done)), |project, id| project.task(task(id)))
+            .build()
+            .unwrap()
+    }
+
+    fn placed(project: &Project) -> Vec<Vec<&str>> {
+        project.columns().iter().map(|column| column.tasks().iter().map(Id::as_ref).collect()).collect()
+    }
+
+    #[test]
+    fn merges_changes_from_both_sides() {
+        let base = board(&["a", "b"], &[]);
+        let mut ours = base.clone();
+        ours.move_task_to_column("a".into(), "done".into());
+        let mut theirs = board(&["a", "b", "c"], &[]);
+        theirs.name = "Website".to_string();
+
+        let merged = ours.merge(Some(&base), &theirs);
+        assert_eq!(placed(&merged), vec![vec!["b", "c"], vec!["a"]]);
+        assert_eq!(merged.tasks().len(), 3);
+        assert_eq!(merged.name(), "Website");
+    }
+
+    #[test]
+    fn keeps_our_side_of_a_conflict() {
+        let base = board(&["a"], &[]);
+        let (mut ours, mut theirs) = (base.clone(), base.clone());
+        ours.name = "Ours".to_string();
+        theirs.name = "Theirs".to_string();
+        ours.move_task_to_column("a".into(), "done".into());
+        theirs.delete_task(&"a".into());
+
+        let merged = ours.merge(Some(&base), &theirs);
+        assert_eq!(merged.name(), "Ours");
+        assert_eq!(placed(&merged), vec![vec![], vec!["a"]]);
+    }
+
+    #[test]
+    fn keeps_tasks_deleted_on_either_side_deleted() {
+        let base = board(&["a", "b"], &[]);
+        let mut ours = base.clone();
+        ours.delete_task(&"a".into());
+        let mut theirs = base.clone();
+        theirs.delete_task(&"b".into());
+
+        let merged = ours.merge(Some(&base), &theirs);
+        assert!(merged.tasks().is_empty());
+        assert_eq!(placed(&merged), vec![Vec::<&str>::new(), vec![]]);
+    }
+
+    #[test]
+    fn keeps_the_highest_task_counter() {
+        let mut base = board(&[], &[]);
+        let mut ours = base.clone();
+        ours.generate_task_id("One");
+        let mut theirs = base.clone();
+        theirs.generate_task_id("One");
+        theirs.generate_task_id("Two");
+        assert_eq!(ours.merge(Some(&base), &theirs).task_counter(), 2);
+        base.generate_task_id("One");
+        assert_eq!(theirs.merge(Some(&base), &ours).task_counter(), 2);
+    }
+}
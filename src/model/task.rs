@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 use super::Id;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Task {
     id: Id,
     tags: Vec<String>,
@@ -48,6 +48,10 @@ impl Task {
     pub fn remove_tag(&mut self, tag: &str) {
         self.tags.retain(|t| t != tag);
     }
+
+    pub fn set_assignee(&mut self, assignee: Option<String>) {
+        self.assignee = assignee;
+    }
 }
 
 #[derive(Debug)]
@@ -96,7 +100,7 @@ impl TaskBuilder {
         }
     }
 
-    pub fn build(self) -> Result<Task, Self> {
+    pub fn build(self) -> Result<Task, Box<Self>> {
         match self {
             TaskBuilder { id, tags, name: Some(name), assignee, description: Some(description) } => Ok(Task {
                 id,
@@ -105,7 +109,7 @@ impl TaskBuilder {
                 assignee,
                 description,
             }),
-            _ => Err(self)
+            _ => Err(Box::new(self))
         } 
     }
 }
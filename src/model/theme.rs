@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use super::Column;
+
+/// How the board looks when it is opened: colours for tags, assignees and columns, and the palette
+/// for the rest of the interface. Colours are names such as `red` or `light blue`, or hex codes
+/// such as `#ff8800`.
+#[derive(Serialize, Deserialize, PartialEq, Clone, Default, Debug)]
+pub struct Theme {
+    /// `dark`, `light`, `terminal`, or the path of a cursive theme file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    palette: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    assignees: BTreeMap<String, String>,
+    /// Colours of columns, by their ID or name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    columns: BTreeMap<String, String>,
+}
+
+impl Theme {
+    pub fn is_empty(&self) -> bool {
+        self == &Theme::default()
+    }
+
+    pub fn palette(&self) -> Option<&str> {
+        self.palette.as_deref()
+    }
+
+    pub fn tag_color(&self, tag: &str) -> Option<&str> {
+        self.tags.get(tag).map(String::as_str)
+    }
+
+    pub fn assignee_color(&self, assignee: &str) -> Option<&str> {
+        self.assignees.get(assignee).map(String::as_str)
+    }
+
+    pub fn column_color(&self, column: &Column) -> Option<&str> {
+        self.columns.get(column.id().as_ref())
+            .or_else(|| self.columns.get(column.name()))
+            .map(String::as_str)
+    }
+
+    pub fn set_tag_color(&mut self, tag: String, color: String) {
+        self.tags.insert(tag, color);
+    }
+
+    pub fn set_assignee_color(&mut self, assignee: String, color: String) {
+        self.assignees.insert(assignee, color);
+    }
+
+    pub fn set_column_color(&mut self, column: String, color: String) {
+        self.columns.insert(column, color);
+    }
+
+    /// All of the colours set, for checking that they are valid.
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.tags.values().chain(self.assignees.values()).chain(self.columns.values()).map(String::as_str)
+    }
+}
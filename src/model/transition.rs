@@ -0,0 +1,274 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use super::{Id, GitProject, Task};
+
+/// A change to a task made by a commit message command. These are recorded in git notes, so
+/// they are written in the same syntax as the commands themselves.
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+pub enum Transition {
+    /// `task@project is to (was from)`
+    Column { project: Id, task: Id, from: Option<Id>, to: Id },
+    /// `task@project +added -removed`
+    Tags { project: Id, task: Id, added: Vec<String>, removed: Vec<String> },
+    /// `task@project =to (was from)`
+    Assign { project: Id, task: Id, from: Option<String>, to: Option<String> },
+    /// `task@project created "name" in column`
+    Create { project: Id, task: Id, name: String, column: Id },
+    /// `task@project deleted "name" from column`, which only arises from reverting a `Create`
+    Delete { project: Id, task: Id, name: String, column: Id },
+}
+
+impl Transition {
+    pub fn project(&self) -> &Id {
+        match self {
+            Transition::Column { project, .. }
+            | Transition::Tags { project, .. }
+            | Transition::Assign { project, .. }
+            | Transition::Create { project, .. }
+            | Transition::Delete { project, .. } => project,
+        }
+    }
+
+    pub fn task(&self) -> &Id {
+        match self {
+            Transition::Column { task, .. }
+            | Transition::Tags { task, .. }
+            | Transition::Assign { task, .. }
+            | Transition::Create { task, .. }
+            | Transition::Delete { task, .. } => task,
+        }
+    }
+
+    /// The transition that undoes this one, if it can be undone.
+    pub fn inverse(&self) -> Option<Transition> {
+        Some(match self.clone() {
+            Transition::Column { project, task, from, to } => Transition::Column { project, task, from: Some(to), to: from? },
+            Transition::Tags { project, task, added, removed } => Transition::Tags { project, task, added: removed, removed: added },
+            Transition::Assign { project, task, from, to } => Transition::Assign { project, task, from: to, to: from },
+            Transition::Create { project, task, name, column } => Transition::Delete { project, task, name, column },
+            Transition::Delete { project, task, name, column } => Transition::Create { project, task, name, column },
+        })
+    }
+
+    /// Applies this transition to a project board, returning whether the board was changed.
+    ///
+    /// Transitions describe the state a task ends up in rather than the steps to get there, so
+    /// applying one that has already been applied does nothing.
+    pub fn apply(&self, git_project: &mut GitProject) -> bool {
+        let task = self.task();
+        let project = match git_project.projects_mut().iter_mut().find(|project| project.id() == self.project()) {
+            Some(project) => project,
+            None => return false,
+        };
+        let before = project.clone();
+
+        match self {
+            Transition::Create { name, column, .. } => {
+                if let Some(column) = project.columns().iter().position(|c| c.id() == column) {
+                    project.add_task(Task::new(task).name(name).description("").build().unwrap(), column);
+                }
+            }
+            Transition::Delete { .. } => project.delete_task(task),
+            _ => {
+                let mut updated = match project.task_with_id(task) {
+                    Some(task) => task.clone(),
+                    None => return false,
+                };
+                match self {
+                    Transition::Column { to, .. } => {
+                        if project.columns().iter().any(|column| column.id() == to) {
+                            project.move_task_to_column(task.clone(), to.clone());
+                        }
+                    }
+                    Transition::Tags { added, removed, .. } => {
+                        for tag in added { updated.add_tag(tag); }
+                        for tag in removed { updated.remove_tag(tag); }
+                        project.replace_task(task, updated, None);
+                    }
+                    Transition::Assign { to, .. } => {
+                        updated.set_assignee(to.clone());
+                        project.replace_task(task, updated, None);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+        *project != before
+    }
+}
+
+/// Escapes a task name to be written between quotes on a single line.
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for ch in name.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// Reverses `escape`.
+fn unescape(escaped: &str) -> String {
+    let mut name = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            name.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => name.push('\n'),
+            Some('r') => name.push('\r'),
+            Some(ch) => name.push(ch),
+            None => name.push('\\'),
+        }
+    }
+    name
+}
+
+impl Display for Transition {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Transition::Column { project, task, from, to } => {
+                write!(f, "{}@{} is {} (was {})", task, project, to, from.as_ref().map(Id::as_ref).unwrap_or("nowhere"))
+            }
+            Transition::Tags { project, task, added, removed } => {
+                write!(f, "{}@{}", task, project)?;
+                for tag in added { write!(f, " +{}", tag)?; }
+                for tag in removed { write!(f, " -{}", tag)?; }
+                Ok(())
+            }
+            Transition::Assign { project, task, from, to } => {
+                write!(f, "{}@{} ={} (was {})", task, project, to.as_deref().unwrap_or("nobody"), from.as_deref().unwrap_or("nobody"))
+            }
+            Transition::Create { project, task, name, column } => {
+                write!(f, "{}@{} created \"{}\" in {}", task, project, escape(name), column)
+            }
+            Transition::Delete { project, task, name, column } => {
+                write!(f, "{}@{} deleted \"{}\" from {}", task, project, escape(name), column)
+            }
+        }
+    }
+}
+
+impl FromStr for Transition {
+    type Err = ();
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let column_format = Regex::new(r"^([^\[\]\s@]+)@([^\[\]\s]+) is ([^\[\]\s]+) \(was ([^\[\]\s]+)\)$").unwrap();
+        let tags_format = Regex::new(r"^([^\[\]\s@]+)@([^\[\]\s]+)((?: [+-][\S]+)+)$").unwrap();
+        let assign_format = Regex::new(r"^([^\[\]\s@]+)@([^\[\]\s]+) =([^\[\]\s]+) \(was ([^\[\]\s]+)\)$").unwrap();
+        let create_format = Regex::new(r#"^([^\[\]\s@]+)@([^\[\]\s]+) created "((?:[^"\\]|\\.)*)" in ([^\[\]\s]+)$"#).unwrap();
+        let delete_format = Regex::new(r#"^([^\[\]\s@]+)@([^\[\]\s]+) deleted "((?:[^"\\]|\\.)*)" from ([^\[\]\s]+)$"#).unwrap();
+
+        let line = line.trim();
+        if let Some(captures) = column_format.captures(line) {
+            let from = &captures[4];
+            return Ok(Transition::Column {
+                task: captures[1].into(),
+                project: captures[2].into(),
+                to: captures[3].into(),
+                from: if from == "nowhere" { None } else { Some(from.into()) },
+            });
+        }
+        if let Some(captures) = tags_format.captures(line) {
+            let tags: Vec<&str> = captures[3].split_whitespace().collect();
+            return Ok(Transition::Tags {
+                task: captures[1].into(),
+                project: captures[2].into(),
+                added: tags.iter().filter_map(|tag| tag.strip_prefix('+')).map(str::to_string).collect(),
+                removed: tags.iter().filter_map(|tag| tag.strip_prefix('-')).map(str::to_string).collect(),
+            });
+        }
+        if let Some(captures) = assign_format.captures(line) {
+            let assignee = |name: &str| if name == "nobody" { None } else { Some(name.to_string()) };
+            return Ok(Transition::Assign {
+                task: captures[1].into(),
+                project: captures[2].into(),
+                to: assignee(&captures[3]),
+                from: assignee(&captures[4]),
+            });
+        }
+        if let Some(captures) = create_format.captures(line) {
+            return Ok(Transition::Create {
+                task: captures[1].into(),
+                project: captures[2].into(),
+                name: unescape(&captures[3]),
+                column: captures[4].into(),
+            });
+        }
+        if let Some(captures) = delete_format.captures(line) {
+            return Ok(Transition::Delete {
+                task: captures[1].into(),
+                project: captures[2].into(),
+                name: unescape(&captures[3]),
+                column: captures[4].into(),
+            });
+        }
+        Err(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(transition: Transition) {
+        let line = transition.to_string();
+        assert_eq!(line.parse::<Transition>(), Ok(transition), "{}", line);
+    }
+
+    #[test]
+    fn every_transition_round_trips() {
+        round_trip(Transition::Column { project: "web".into(), task: "t-1".into(), from: Some("todo".into()), to: "done".into() });
+        round_trip(Transition::Column { project: "web".into(), task: "t-1".into(), from: None, to: "done".into() });
+        round_trip(Transition::Tags { project: "web".into(), task: "t-1".into(), added: vec!["bug".into(), "ui".into()], removed: vec!["wip".into()] });
+        round_trip(Transition::Tags { project: "web".into(), task: "t-1".into(), added: vec![], removed: vec!["wip".into()] });
+        round_trip(Transition::Assign { project: "web".into(), task: "t-1".into(), from: Some("ana".into()), to: Some("bo@example.com".into()) });
+        round_trip(Transition::Assign { project: "web".into(), task: "t-1".into(), from: None, to: None });
+        round_trip(Transition::Create { project: "web".into(), task: "t-1".into(), name: "Login form".into(), column: "todo".into() });
+        round_trip(Transition::Delete { project: "web".into(), task: "t-1".into(), name: "Login form".into(), column: "todo".into() });
+    }
+
+    #[test]
+    fn inverses_round_trip() {
+        let column = Transition::Column { project: "web".into(), task: "t-1".into(), from: Some("todo".into()), to: "done".into() };
+        let tags = Transition::Tags { project: "web".into(), task: "t-1".into(), added: vec!["bug".into()], removed: vec![] };
+        let assign = Transition::Assign { project: "web".into(), task: "t-1".into(), from: None, to: Some("ana".into()) };
+        let create = Transition::Create { project: "web".into(), task: "t-1".into(), name: "Login form".into(), column: "todo".into() };
+        for transition in [column, tags, assign, create].iter().cloned() {
+            let inverse = transition.inverse().unwrap();
+            round_trip(inverse.clone());
+            assert_eq!(inverse.inverse(), Some(transition));
+        }
+        assert_eq!(Transition::Column { project: "web".into(), task: "t-1".into(), from: None, to: "done".into() }.inverse(), None);
+    }
+
+    #[test]
+    fn rejects_other_lines() {
+        assert_eq!("Fix the login form".parse::<Transition>(), Err(()));
+        assert_eq!("t-1@web is done".parse::<Transition>(), Err(()));
+        assert_eq!(r#"t-1@web created "unterminated in todo"#.parse::<Transition>(), Err(()));
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for name in &["Login form", r#"Say "hello""#, r"C:\path\", r#"\"#, r#"ends with \"#, "two\nlines", "tab\tand ünicode"] {
+            round_trip(Transition::Create { project: "web".into(), task: "t-1".into(), name: name.to_string(), column: "todo".into() });
+            round_trip(Transition::Delete { project: "web".into(), task: "t-1".into(), name: name.to_string(), column: "todo".into() });
+        }
+    }
+
+    #[test]
+    fn names_are_written_on_one_line() {
+        let transition = Transition::Create { project: "web".into(), task: "t-1".into(), name: "a\nb \"c\"".into(), column: "todo".into() };
+        assert_eq!(transition.to_string(), r#"t-1@web created "a\nb \"c\"" in todo"#);
+    }
+}